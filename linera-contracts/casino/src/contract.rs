@@ -4,18 +4,23 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
 mod state;
-mod games;
 
-use casino::{CasinoAbi, CasinoOperation, CasinoResponse, GameType, PendingGame, GameOutcome};
+use std::collections::BTreeMap;
+
+use casino::{
+    Asset, BlackjackParams, BlackjackRound, CasinoAbi, CasinoError, CasinoOperation, CasinoParameters, CasinoResponse, CrashBet,
+    CrashParams, CrashPhase, CrashRound, GameParams, GameResult, GameType, PendingGame, GameOutcome, HouseSeedChain,
+    Ledger, MinesRound, Opening, PlayerSession, Rejection, SessionBet, TableLimits, BASIS_POINTS, reveal_deadline,
+    games::{self, blackjack, commitment, crash, entropy::ALGORITHM_VERSION, mines::{self, Mines}, randomness, Game},
+};
 use linera_sdk::{
-    linera_base_types::{WithContractAbi, Amount},
+    abis::fungible::{FungibleOperation, FungibleResponse, FungibleTokenAbi},
+    linera_base_types::{WithContractAbi, Account, AccountOwner, Amount, Timestamp},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
-use sha3::{Digest, Sha3_256};
 
 use self::state::CasinoState;
-use self::games::{roulette, plinko, mines, wheel};
 
 pub struct CasinoContract {
     state: CasinoState,
@@ -31,7 +36,7 @@ impl WithContractAbi for CasinoContract {
 impl Contract for CasinoContract {
     type Message = ();
     type InstantiationArgument = u64;  // Simple initialization value
-    type Parameters = ();
+    type Parameters = CasinoParameters;
     type EventValue = ();
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
@@ -44,176 +49,1608 @@ impl Contract for CasinoContract {
     async fn instantiate(&mut self, initial_funds: u64) {
         // Initialize the casino state
         self.state.next_game_id.set(1);
-        self.state.total_funds.set(initial_funds);
+        // The instantiating owner runs the house side of commit-reveal
+        let creator = self.runtime.authenticated_signer();
+        self.state.house_operator.set(creator);
+
+        // A casino without accepted tokens plays in the native token, with no limits by default.
+        // Casinos accepting tokens open their tables by hand; an initial native bankroll backs
+        // the native table once it is opened, and can be withdrawn until then.
+        if self.runtime.application_parameters().tokens.is_empty() {
+            let limits = TableLimits {
+                min_bet: Amount::ZERO,
+                max_bet: Amount::MAX,
+                max_exposure_bps: BASIS_POINTS,
+            };
+            self.state.tables.insert(&Asset::Native, limits)
+                .expect("Failed to open native table");
+        }
+
+        // The initial native bankroll is moved from the creator into the application's custody
+        if initial_funds > 0 {
+            let creator = creator.expect("Funding the casino requires an authenticated creator");
+            self.receive_funds(Asset::Native, creator, Amount::from_attos(initial_funds as u128));
+            self.update_ledger(Asset::Native, |ledger| ledger.fund_house(initial_funds as u128)).await;
+        }
+        self.assert_custody_covered(Asset::Native).await;
     }
 
     async fn execute_operation(&mut self, operation: CasinoOperation) -> CasinoResponse {
-        match operation {
-            CasinoOperation::Deposit { amount } => {
-                let player = self.runtime.authenticated_signer()
-                    .expect("Deposit must be from authenticated user");
+        match self.execute(operation).await {
+            Ok(response) => response,
+            Err(error) => {
+                // Every check runs before the first write, so a rejected operation changed
+                // nothing; remember why it failed for the signer to look up
+                if let Some(owner) = self.runtime.authenticated_signer() {
+                    let rejection = Rejection {
+                        error: error.clone(),
+                        timestamp: self.runtime.system_time(),
+                    };
+                    self.state.last_rejections.insert(&owner, rejection)
+                        .expect("Failed to record rejection");
+                }
+                CasinoResponse::Error(error)
+            }
+        }
+    }
 
-                let deposit_attos = amount.to_attos();
+    async fn execute_message(&mut self, _message: ()) {
+        panic!("Casino application doesn't support cross-chain messages");
+    }
 
-                // Get current player balance
-                let current_balance = self.state.player_balances.get(&player)
-                    .await
-                    .expect("Failed to read balance")
-                    .unwrap_or(0);
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
 
-                // Update player balance
-                let new_balance = current_balance.saturating_add(deposit_attos);
-                self.state.player_balances.insert(&player, new_balance)
-                    .expect("Failed to update balance");
+impl CasinoContract {
+    /// Executes an operation, validating it completely before changing any state
+    async fn execute(&mut self, operation: CasinoOperation) -> Result<CasinoResponse, CasinoError> {
+        match operation {
+            CasinoOperation::Deposit { asset, amount } => {
+                let player = self.signer()?;
+                // Only assets with an open table can be deposited
+                self.check_asset_supported(asset)?;
+                self.table_limits(asset).await?;
 
-                // Update total funds
-                let current_funds = *self.state.total_funds.get();
-                self.state.total_funds.set(current_funds.saturating_add(deposit_attos as u64));
+                // Move the tokens from the player's account into the application's custody
+                self.receive_funds(asset, player, amount);
+                self.update_ledger(asset, |ledger| ledger.deposit(amount.to_attos())).await;
+                let new_balance = self.credit_player(player, asset, amount.to_attos()).await;
+                self.assert_custody_covered(asset).await;
 
-                CasinoResponse::DepositSuccess {
+                Ok(CasinoResponse::DepositSuccess {
                     new_balance: Amount::from_attos(new_balance)
-                }
+                })
             }
-            CasinoOperation::Withdraw { amount } => {
-                let player = self.runtime.authenticated_signer()
-                    .expect("Withdraw must be from authenticated user");
-
-                let withdraw_attos = amount.to_attos();
-
-                // Get current player balance
-                let current_balance = self.state.player_balances.get(&player)
-                    .await
-                    .expect("Failed to read balance")
-                    .unwrap_or(0);
-
-                // Check sufficient balance
-                assert!(current_balance >= withdraw_attos, "Insufficient balance");
+            CasinoOperation::Withdraw { asset, amount, destination } => {
+                let player = self.signer()?;
 
-                // Update player balance
-                let new_balance = current_balance.saturating_sub(withdraw_attos);
-                self.state.player_balances.insert(&player, new_balance)
-                    .expect("Failed to update balance");
+                let new_balance = self.debit_player(player, asset, amount.to_attos()).await?;
+                self.update_ledger(asset, |ledger| ledger.withdraw(amount.to_attos())).await;
 
-                // Update total funds
-                let current_funds = *self.state.total_funds.get();
-                self.state.total_funds.set(current_funds.saturating_sub(withdraw_attos as u64));
+                // Pay the tokens out of the application's custody
+                self.pay_out(asset, destination, amount);
+                self.assert_custody_covered(asset).await;
 
-                CasinoResponse::WithdrawSuccess {
+                Ok(CasinoResponse::WithdrawSuccess {
                     new_balance: Amount::from_attos(new_balance)
-                }
+                })
             }
             CasinoOperation::PlaceBet {
-                game_type,
+                asset,
                 bet_amount,
                 commit_hash,
-                game_params,
+                house_chain_tip,
+                mut game_params,
             } => {
                 let game_id = *self.state.next_game_id.get();
-                let player = self.runtime.authenticated_signer()
-                    .expect("Bet must be placed by authenticated user");
+                let player = self.signer()?;
                 let timestamp = self.runtime.system_time();
+                if let GameParams::Crash(_) = game_params {
+                    return Err(CasinoError::CrashRoundRequired);
+                }
 
-                let bet_attos = bet_amount.to_attos();
+                let game = games::game_for(&game_params);
+                self.bind_house_edge(&mut game_params).await;
+                game.validate(&game_params)?;
+                game.check_stake(&game_params, bet_amount)?;
+                // Both seeds are revealed before settling, which would show the player the
+                // outcome of a game they are still playing
+                if game.is_interactive() {
+                    return Err(CasinoError::SessionRequired);
+                }
+                self.check_bet_within_limits(asset, bet_amount).await?;
 
-                // Check player has sufficient balance
-                let current_balance = self.state.player_balances.get(&player)
+                // The bet is bound to the next seed of the active house chain, whose tip was
+                // published before the player chose their seed
+                let house_epoch = *self.state.house_epoch.get();
+                let mut house_chain = self.state.house_chains.get(&house_epoch)
                     .await
-                    .expect("Failed to read balance")
-                    .unwrap_or(0);
-                assert!(current_balance >= bet_attos, "Insufficient balance to place bet");
+                    .expect("Failed to read house chain")
+                    .ok_or(CasinoError::NoHouseChain)?;
+                if house_chain.tip != house_chain_tip {
+                    return Err(CasinoError::StaleHouseChain);
+                }
+                if house_chain.is_exhausted() {
+                    return Err(CasinoError::HouseChainExhausted);
+                }
+                let liability = self.bet_liability(asset, bet_amount, &game_params).await?;
 
                 // Deduct bet from player balance
-                let new_balance = current_balance.saturating_sub(bet_attos);
-                self.state.player_balances.insert(&player, new_balance)
-                    .expect("Failed to update balance");
+                self.debit_player(player, asset, bet_amount.to_attos()).await?;
+                self.update_ledger(asset, |ledger| ledger.lock_bet(bet_amount.to_attos(), liability.to_attos())).await;
+
+                house_chain.assigned += 1;
+                let house_index = house_chain.assigned;
+                self.state.house_chains.insert(&house_epoch, house_chain)
+                    .expect("Failed to update house chain");
 
                 let pending_game = PendingGame {
                     player,
-                    game_type,
+                    asset,
                     bet_amount,
                     commit_hash,
+                    house_epoch,
+                    house_index,
                     game_params,
                     timestamp,
+                    liability,
+                    player_seed: None,
+                    house_seed: None,
                 };
 
                 self.state.pending_games.insert(&game_id, pending_game)
                     .expect("Failed to insert pending game");
                 self.state.next_game_id.set(game_id + 1);
 
-                CasinoResponse::GamePlaced { game_id }
+                Ok(CasinoResponse::GamePlaced { game_id })
             }
             CasinoOperation::Reveal {
                 game_id,
                 reveal_value,
             } => {
-                let player = self.runtime.authenticated_signer()
-                    .expect("Reveal must be from authenticated user");
-
-                let pending_game = self.state.pending_games.get(&game_id)
-                    .await
-                    .expect("Failed to read pending game")
-                    .expect("Game not found");
+                let player = self.signer()?;
+                let mut pending_game = self.pending_game(game_id).await?;
 
                 // Verify the player owns this game
-                assert_eq!(pending_game.player, player, "Only game owner can reveal");
+                if pending_game.player != player {
+                    return Err(CasinoError::NotGameOwner);
+                }
+                if pending_game.player_seed.is_some() {
+                    return Err(CasinoError::AlreadyRevealed);
+                }
 
                 // Verify the commit hash
-                let mut hasher = Sha3_256::new();
-                hasher.update(reveal_value);
-                let calculated_hash: [u8; 32] = hasher.finalize().into();
-                assert_eq!(calculated_hash, pending_game.commit_hash, "Invalid reveal value");
-
-                // Calculate game outcome based on game type
-                let (outcome, multiplier) = match pending_game.game_type {
-                    GameType::Roulette => roulette::calculate_outcome(&reveal_value, &pending_game.game_params),
-                    GameType::Plinko => plinko::calculate_outcome(&reveal_value, &pending_game.game_params),
-                    GameType::Mines => mines::calculate_outcome(&reveal_value, &pending_game.game_params),
-                    GameType::Wheel => wheel::calculate_outcome(&reveal_value, &pending_game.game_params),
-                };
+                if !randomness::verify_commit(&reveal_value, &pending_game.commit_hash) {
+                    return Err(CasinoError::InvalidReveal);
+                }
 
-                // Calculate payout (multiplier is percentage, e.g., 200 = 2x)
-                let bet_attos = pending_game.bet_amount.to_attos();
-                let payout_attos = bet_attos * multiplier as u128 / 100;
-                let payout = Amount::from_attos(payout_attos);
+                pending_game.player_seed = Some(reveal_value);
+                Ok(self.settle_or_store(game_id, pending_game).await)
+            }
+            CasinoOperation::InstallHouseChain { tip, length } => {
+                self.check_house_operator()?;
+                if length == 0 {
+                    return Err(CasinoError::EmptyHouseChain);
+                }
 
-                // Credit payout to player balance
-                let current_balance = self.state.player_balances.get(&player)
+                let current_epoch = *self.state.house_epoch.get();
+                let current_chain = self.state.house_chains.get(&current_epoch)
                     .await
-                    .expect("Failed to read balance")
-                    .unwrap_or(0);
-                let new_balance = current_balance.saturating_add(payout_attos);
-                self.state.player_balances.insert(&player, new_balance)
-                    .expect("Failed to update balance");
-
-                // Record game outcome
-                let game_outcome = GameOutcome {
-                    game_id,
-                    game_type: format!("{:?}", pending_game.game_type),
-                    bet_amount: pending_game.bet_amount.to_string(),
-                    payout_amount: payout.to_string(),
-                    outcome_details: outcome.clone(),
-                    timestamp: pending_game.timestamp.micros(),
-                };
-                self.state.game_history.push(game_outcome);
+                    .expect("Failed to read house chain");
+                if current_chain.is_some_and(|chain| !chain.is_exhausted()) {
+                    return Err(CasinoError::HouseChainInUse);
+                }
+
+                let epoch = current_epoch + 1;
+                self.state.house_chains.insert(&epoch, HouseSeedChain::new(epoch, tip, length))
+                    .expect("Failed to insert house chain");
+                self.state.house_epoch.set(epoch);
 
-                // Remove pending game
+                Ok(CasinoResponse::HouseChainInstalled { epoch })
+            }
+            CasinoOperation::RevealHouseSeed {
+                game_id,
+                house_seed,
+            } => {
+                self.check_house_operator()?;
+                let mut pending_game = self.pending_game(game_id).await?;
+
+                if pending_game.house_seed.is_some() {
+                    return Err(CasinoError::AlreadyRevealed);
+                }
+
+                // House seeds are revealed in chain order, each one hashing to the previous link
+                let mut house_chain = self.house_chain(pending_game.house_epoch).await;
+                house_chain.reveal(pending_game.house_index, house_seed)?;
+                self.state.house_chains.insert(&pending_game.house_epoch, house_chain)
+                    .expect("Failed to update house chain");
+
+                pending_game.house_seed = Some(house_seed);
+                Ok(self.settle_or_store(game_id, pending_game).await)
+            }
+            CasinoOperation::ClaimTimeout { game_id } => {
+                if let Some(bet) = self.state.session_bets.get(&game_id).await.expect("Failed to read session bet") {
+                    return self.time_out_session_bet(game_id, bet).await;
+                }
+                if let Some(mut round) = self.state.mines_rounds.get(&game_id).await.expect("Failed to read mines round") {
+                    let Some(awaiting_house_since) = round.awaiting_house_since else {
+                        // A player who stopped playing is cashed out, freeing the round's liability
+                        // once the house reveals its seed
+                        round.time_out_player(self.runtime.system_time())?;
+                        self.state.mines_rounds.insert(&game_id, round)
+                            .expect("Failed to update mines round");
+                        return Ok(CasinoResponse::AwaitingHouse { game_id });
+                    };
+                    self.time_out_round(round.player, round.nonce, awaiting_house_since).await?;
+                    self.state.mines_rounds.remove(&game_id)
+                        .expect("Failed to remove mines round");
+                    return Ok(self.settle_timed_out(game_id, round.player, round.asset, round.bet_amount, round.liability, true).await);
+                }
+                if let Some(mut round) = self.state.blackjack_rounds.get(&game_id).await.expect("Failed to read blackjack round") {
+                    let Some(awaiting_house_since) = round.awaiting_house_since else {
+                        // A player who stopped playing stands, and the house deals on as usual
+                        round.time_out_player(self.runtime.system_time())?;
+                        self.state.blackjack_rounds.insert(&game_id, round)
+                            .expect("Failed to update blackjack round");
+                        return Ok(CasinoResponse::AwaitingHouse { game_id });
+                    };
+                    self.time_out_round(round.player, round.nonce, awaiting_house_since).await?;
+                    self.state.blackjack_rounds.remove(&game_id)
+                        .expect("Failed to remove blackjack round");
+                    return Ok(self.settle_timed_out(game_id, round.player, round.asset, round.staked, round.liability, true).await);
+                }
+                let pending_game = self.pending_game(game_id).await?;
+                let deadline = reveal_deadline(pending_game.timestamp);
+                if self.runtime.system_time() < deadline {
+                    return Err(CasinoError::RevealDeadlineNotReached { deadline });
+                }
+
+                // The house reveals without seeing the player's seed, so withholding it can only
+                // be to dodge a loss: the player is paid as if the bet had won its most. A player
+                // who saw the house seed and kept theirs has lost.
+                let won = pending_game.house_seed.is_none();
+                if won {
+                    let mut house_chain = self.house_chain(pending_game.house_epoch).await;
+                    house_chain.timed_out.push(pending_game.house_index);
+                    self.state.house_chains.insert(&pending_game.house_epoch, house_chain)
+                        .expect("Failed to update house chain");
+                }
                 self.state.pending_games.remove(&game_id)
                     .expect("Failed to remove pending game");
 
-                CasinoResponse::GameCompleted {
+                Ok(self.settle_timed_out(
                     game_id,
-                    outcome,
-                    payout,
+                    pending_game.player,
+                    pending_game.asset,
+                    pending_game.bet_amount,
+                    pending_game.liability,
+                    won,
+                ).await)
+            }
+            CasinoOperation::SetClientSeed { client_seed } => {
+                let player = self.signer()?;
+                check_client_seed(&client_seed)?;
+
+                let mut session = self.session(&player).await;
+                if session.nonce() != 0 {
+                    return Err(CasinoError::SessionInProgress);
+                }
+                session.client_seed = client_seed;
+                self.state.sessions.insert(&player, session)
+                    .expect("Failed to update session");
+
+                Ok(CasinoResponse::SessionUpdated)
+            }
+            CasinoOperation::CommitServerSeed { player, server_seed_hash, length } => {
+                self.check_house_operator()?;
+                if length == 0 {
+                    return Err(CasinoError::EmptyHouseChain);
+                }
+
+                // Only the tip is published; the seeds stay with the house until their bets
+                // settle. Session chains aren't numbered in epochs.
+                let server_chain = Some(HouseSeedChain::new(0, server_seed_hash, length));
+                let mut session = self.session(&player).await;
+                if session.server_chain.is_none() {
+                    session.server_chain = server_chain;
+                } else if session.next_server_chain.is_none() {
+                    session.next_server_chain = server_chain;
+                } else {
+                    return Err(CasinoError::NextServerSeedCommitted);
+                }
+                self.state.sessions.insert(&player, session)
+                    .expect("Failed to update session");
+
+                Ok(CasinoResponse::SessionUpdated)
+            }
+            CasinoOperation::PlaceSessionBet {
+                asset,
+                bet_amount,
+                mut game_params,
+            } => {
+                let game_id = *self.state.next_game_id.get();
+                let player = self.signer()?;
+                let timestamp = self.runtime.system_time();
+
+                let mut session = self.session(&player).await;
+                if session.client_seed.is_empty() {
+                    return Err(CasinoError::ClientSeedNotSet);
+                }
+                let mut server_chain = session.server_chain.clone().ok_or(CasinoError::NoServerSeed)?;
+                if server_chain.is_exhausted() {
+                    return Err(CasinoError::HouseChainExhausted);
+                }
+                // A round in play reveals its server seed when it ends and later seeds wait for
+                // it, so the session takes no other bet meanwhile
+                if session.open_rounds > 0 {
+                    return Err(CasinoError::GameInProgress);
+                }
+                match &mut game_params {
+                    GameParams::Crash(_) => return Err(CasinoError::CrashRoundRequired),
+                    GameParams::Blackjack(params) => {
+                        let rules = *self.state.blackjack_rules.get();
+                        if params.rules != rules {
+                            return Err(CasinoError::BlackjackRulesMismatch { rules });
+                        }
+                        params.stake = bet_amount;
+                    }
+                    _ => {}
+                }
+                self.bind_house_edge(&mut game_params).await;
+                let game = games::game_for(&game_params);
+                game.validate(&game_params)?;
+                game.check_stake(&game_params, bet_amount)?;
+                self.check_bet_within_limits(asset, bet_amount).await?;
+                let liability = self.bet_liability(asset, bet_amount, &game_params).await?;
+
+                // Deduct bet from player balance
+                self.debit_player(player, asset, bet_amount.to_attos()).await?;
+                self.update_ledger(asset, |ledger| ledger.lock_bet(bet_amount.to_attos(), liability.to_attos())).await;
+
+                // The bet is bound to the next seed of the session's chain
+                let nonce = server_chain.assigned;
+                server_chain.assigned += 1;
+                let bet = SessionBet {
+                    player,
+                    asset,
+                    bet_amount,
+                    game_params,
+                    timestamp,
+                    liability,
+                    client_seed: session.client_seed.clone(),
+                    server_seed_hash: server_chain.tip,
+                    nonce,
+                };
+                session.server_chain = Some(server_chain);
+                if game.is_interactive() {
+                    session.open_rounds += 1;
+                } else {
+                    session.pending_bets += 1;
+                }
+                self.state.sessions.insert(&player, session)
+                    .expect("Failed to update session");
+                self.state.session_bets.insert(&game_id, bet)
+                    .expect("Failed to insert session bet");
+                self.state.next_game_id.set(game_id + 1);
+
+                Ok(CasinoResponse::GamePlaced { game_id })
+            }
+            CasinoOperation::RevealServerSeed { game_id, server_seed } => {
+                self.check_house_operator()?;
+                if let Some(round) = self.state.mines_rounds.get(&game_id).await.expect("Failed to read mines round") {
+                    return self.end_mines_round(game_id, round, server_seed).await;
+                }
+                if let Some(round) = self.state.blackjack_rounds.get(&game_id).await.expect("Failed to read blackjack round") {
+                    return self.end_blackjack_round(game_id, round, server_seed).await;
+                }
+                let bet = self.session_bet(game_id).await?;
+                // Interactive rounds are dealt by their root, and their seed waits for the round to end
+                if games::game_for(&bet.game_params).is_interactive() {
+                    return Err(CasinoError::RoundNotFinished);
+                }
+
+                // Server seeds are revealed in chain order, each one hashing to the previous link
+                let mut session = self.session(&bet.player).await;
+                session.server_chain.as_mut()
+                    .expect("Sessions keep their chain while a bet is pending")
+                    .reveal(bet.nonce + 1, server_seed)?;
+                self.state.session_bets.remove(&game_id)
+                    .expect("Failed to remove session bet");
+
+                let SessionBet {
+                    player,
+                    asset,
+                    bet_amount,
+                    game_params,
+                    timestamp,
+                    liability,
+                    client_seed,
+                    server_seed_hash,
+                    nonce,
+                } = bet;
+                let entropy = randomness::session_entropy(&server_seed, &client_seed, nonce);
+                session.pending_bets -= 1;
+                self.state.sessions.insert(&player, session)
+                    .expect("Failed to update session");
+
+                Ok(self.settle(Settlement {
+                    game_id,
+                    player,
+                    asset,
+                    bet_amount,
+                    game_params,
+                    timestamp,
+                    liability,
+                    entropy,
+                    seeds: SeedRecord::Session {
+                        client_seed,
+                        server_seed_hash,
+                        nonce,
+                        server_seed,
+                    },
+                }).await)
+            }
+            CasinoOperation::DealRound { game_id, root, openings } => {
+                self.check_house_operator()?;
+                let bet = self.session_bet(game_id).await?;
+                let params = match bet.game_params {
+                    GameParams::Mines(params) => params,
+                    GameParams::Blackjack(params) => {
+                        return self.deal_blackjack_round(game_id, bet, params, root, openings).await;
+                    }
+                    _ => return Err(CasinoError::NoRoundToDeal),
+                };
+                // Mines boards show nothing until the player reveals a cell
+                if !openings.is_empty() {
+                    return Err(CasinoError::InvalidOpening);
+                }
+
+                let round = MinesRound {
+                    player: bet.player,
+                    asset: bet.asset,
+                    bet_amount: bet.bet_amount,
+                    timestamp: bet.timestamp,
+                    liability: bet.liability,
+                    params,
+                    multiplier: 0,
+                    board_root: root,
+                    pending_cell: None,
+                    finished: false,
+                    awaiting_house_since: None,
+                    last_action_at: self.runtime.system_time(),
+                    client_seed: bet.client_seed,
+                    server_seed_hash: bet.server_seed_hash,
+                    nonce: bet.nonce,
+                };
+                self.state.session_bets.remove(&game_id)
+                    .expect("Failed to remove session bet");
+                self.state.mines_rounds.insert(&game_id, round)
+                    .expect("Failed to insert mines round");
+
+                Ok(CasinoResponse::RoundDealt { game_id })
+            }
+            CasinoOperation::AnswerRound { game_id, openings } => {
+                self.check_house_operator()?;
+                if let Some(round) = self.state.blackjack_rounds.get(&game_id).await.expect("Failed to read blackjack round") {
+                    return self.answer_blackjack_round(game_id, round, openings).await;
+                }
+                let mut round = self.state.mines_rounds.get(&game_id)
+                    .await
+                    .expect("Failed to read mines round")
+                    .ok_or(CasinoError::GameNotFound { game_id })?;
+                let Some(cell) = round.pending_cell else {
+                    return Err(CasinoError::AwaitingPlayer);
+                };
+                let [opening] = openings.as_slice() else {
+                    return Err(CasinoError::InvalidOpening);
+                };
+                if opening.position != cell as u32 || opening.value > 1 || !commitment::verify(&round.board_root, opening) {
+                    return Err(CasinoError::InvalidOpening);
+                }
+
+                round.pending_cell = None;
+                round.params.picks.push(cell);
+                let hit_mine = opening.value == 1;
+                let cleared = round.params.picks.len() as u32 == mines::safe_cells(&round.params);
+                round.multiplier = if hit_mine {
+                    0
+                } else {
+                    mines::cash_out_multiplier(&round.params, round.params.picks.len() as u32)
+                };
+                // A mine or a cleared board ends the round, which then waits for its server seed
+                round.finished = hit_mine || cleared;
+                round.last_action_at = self.runtime.system_time();
+                round.awaiting_house_since = round.finished.then_some(round.last_action_at);
+                let multiplier = round.multiplier;
+                self.state.mines_rounds.insert(&game_id, round)
+                    .expect("Failed to update mines round");
+
+                Ok(CasinoResponse::TileRevealed { game_id, cell, multiplier })
+            }
+            CasinoOperation::RevealTile { game_id, cell } => {
+                let player = self.signer()?;
+                let mut round = self.mines_round(game_id, player).await?;
+                if round.awaiting_house_since.is_some() {
+                    return Err(CasinoError::AwaitingHouse);
+                }
+                if cell as u32 >= mines::total_cells(&round.params) {
+                    return Err(CasinoError::InvalidCell { cell });
+                }
+                if round.params.picks.contains(&cell) {
+                    return Err(CasinoError::CellAlreadyRevealed { cell });
+                }
+
+                round.pending_cell = Some(cell);
+                round.last_action_at = self.runtime.system_time();
+                round.awaiting_house_since = Some(round.last_action_at);
+                self.state.mines_rounds.insert(&game_id, round)
+                    .expect("Failed to update mines round");
+
+                Ok(CasinoResponse::AwaitingHouse { game_id })
+            }
+            CasinoOperation::CashOut { game_id } => {
+                let player = self.signer()?;
+                let mut round = self.mines_round(game_id, player).await?;
+                if round.awaiting_house_since.is_some() {
+                    return Err(CasinoError::AwaitingHouse);
+                }
+                if round.params.picks.is_empty() {
+                    return Err(CasinoError::NoTilesRevealed);
+                }
+
+                // The round settles once the house reveals the seed that dealt the board
+                round.finished = true;
+                round.last_action_at = self.runtime.system_time();
+                round.awaiting_house_since = Some(round.last_action_at);
+                self.state.mines_rounds.insert(&game_id, round)
+                    .expect("Failed to update mines round");
+
+                Ok(CasinoResponse::AwaitingHouse { game_id })
+            }
+            CasinoOperation::PlayBlackjack { game_id, action } => {
+                let player = self.signer()?;
+                let mut round = self.blackjack_round(game_id, player).await?;
+                if round.awaiting_house_since.is_some() {
+                    return Err(CasinoError::AwaitingHouse);
+                }
+                round.params.actions.push(action);
+                let table = blackjack::play_opened(&round.cards, &round.params).map_err(CasinoError::InvalidBlackjackAction)?;
+
+                // Doubles, splits and insurance stake more of the player's balance, and lock
+                // more of the bankroll for what they can win
+                let (stake, liability) = blackjack::action_stake(action);
+                if stake > 0 {
+                    let stake = Amount::from_attos(round.params.stake.to_attos() * stake as u128 / 100);
+                    let liability = Amount::from_attos(round.params.stake.to_attos() * liability as u128 / 100);
+                    self.check_exposure(round.asset, liability).await?;
+                    self.debit_player(player, round.asset, stake.to_attos()).await?;
+                    self.update_ledger(round.asset, |ledger| ledger.lock_bet(stake.to_attos(), liability.to_attos())).await;
+                    round.staked = round.staked.saturating_add(stake);
+                    round.liability = round.liability.saturating_add(liability);
+                }
+
+                // Cards the action draws are opened by the house, and once the player is done the
+                // dealer plays from the revealed server seed
+                let awaiting_house = table.waiting.is_some() || table.finished;
+                round.last_action_at = self.runtime.system_time();
+                round.awaiting_house_since = awaiting_house.then_some(round.last_action_at);
+                self.state.blackjack_rounds.insert(&game_id, round)
+                    .expect("Failed to update blackjack round");
+
+                Ok(if awaiting_house {
+                    CasinoResponse::AwaitingHouse { game_id }
+                } else {
+                    CasinoResponse::BlackjackHandUpdated { game_id }
+                })
+            }
+            CasinoOperation::SetBlackjackRules { rules } => {
+                self.check_house_operator()?;
+                self.state.blackjack_rules.set(rules);
+
+                Ok(CasinoResponse::BlackjackRulesUpdated)
+            }
+            CasinoOperation::OpenCrashRound { seed_hash } => {
+                self.check_house_operator()?;
+                let latest_round = *self.state.crash_round_id.get();
+                if latest_round > 0 && self.crash_round(latest_round).await?.deadline().is_some() {
+                    return Err(CasinoError::CrashRoundInProgress);
+                }
+
+                let round_id = latest_round + 1;
+                let round = CrashRound {
+                    phase: CrashPhase::Open,
+                    seed_hash,
+                    house_edge_bps: self.house_edge(GameType::Crash).await.expect("Crash pays at a house edge"),
+                    opened_at: self.runtime.system_time(),
+                    started_at: None,
+                    crashed_at: None,
+                    seed: None,
+                    crash_point: None,
+                    bets: Vec::new(),
+                };
+                self.state.crash_rounds.insert(&round_id, round)
+                    .expect("Failed to insert crash round");
+                self.state.crash_round_id.set(round_id);
+
+                Ok(CasinoResponse::CrashRoundOpened { round_id })
+            }
+            CasinoOperation::PlaceCrashBet { round_id, asset, bet_amount, auto_cash_out } => {
+                let game_id = *self.state.next_game_id.get();
+                let player = self.signer()?;
+                let timestamp = self.runtime.system_time();
+
+                let mut round = self.crash_round_in(round_id, CrashPhase::Open).await?;
+                if round.bets.len() >= crash::MAX_ROUND_BETS {
+                    return Err(CasinoError::CrashRoundFull);
+                }
+                // Every bet rides the same curve, so they all pay at the edge the round opened with
+                let params = CrashParams {
+                    auto_cash_out,
+                    cashed_out_at: None,
+                    house_edge_bps: round.house_edge_bps,
+                };
+                let game_params = GameParams::Crash(params.clone());
+                games::game_for(&game_params).validate(&game_params)?;
+                self.check_bet_within_limits(asset, bet_amount).await?;
+                let liability = self.bet_liability(asset, bet_amount, &game_params).await?;
+
+                // Deduct bet from player balance
+                self.debit_player(player, asset, bet_amount.to_attos()).await?;
+                self.update_ledger(asset, |ledger| ledger.lock_bet(bet_amount.to_attos(), liability.to_attos())).await;
+
+                round.bets.push(CrashBet {
+                    game_id,
+                    player,
+                    asset,
+                    bet_amount,
+                    timestamp,
+                    liability,
+                    params,
+                });
+                self.state.crash_rounds.insert(&round_id, round)
+                    .expect("Failed to update crash round");
+                self.state.next_game_id.set(game_id + 1);
+
+                Ok(CasinoResponse::GamePlaced { game_id })
+            }
+            CasinoOperation::StartCrashRound { round_id } => {
+                self.check_house_operator()?;
+                let mut round = self.crash_round_in(round_id, CrashPhase::Open).await?;
+
+                round.phase = CrashPhase::Running;
+                round.started_at = Some(self.runtime.system_time());
+                self.state.crash_rounds.insert(&round_id, round)
+                    .expect("Failed to update crash round");
+
+                Ok(CasinoResponse::CrashRoundStarted { round_id })
+            }
+            CasinoOperation::CashOutCrash { round_id } => {
+                let player = self.signer()?;
+                let mut round = self.crash_round_in(round_id, CrashPhase::Running).await?;
+                let started_at = round.started_at.expect("Running crash rounds have started");
+                // The curve is read at the block's timestamp; whether it had crashed by then is
+                // only known once the seed is revealed
+                let elapsed = self.runtime.system_time().micros().saturating_sub(started_at.micros());
+                let multiplier = crash::curve_multiplier(elapsed);
+
+                let mut has_bet = false;
+                let mut cashed_out = false;
+                for bet in round.bets.iter_mut().filter(|bet| bet.player == player) {
+                    has_bet = true;
+                    // Bets whose auto target the curve has passed already left it there
+                    let auto_cashed_out = bet.params.auto_cash_out.is_some_and(|target| target <= multiplier);
+                    if bet.params.cashed_out_at.is_none() && !auto_cashed_out {
+                        bet.params.cashed_out_at = Some(multiplier);
+                        cashed_out = true;
+                    }
+                }
+                if !has_bet {
+                    return Err(CasinoError::NoCrashBet);
+                }
+                if !cashed_out {
+                    return Err(CasinoError::AlreadyCashedOut);
+                }
+                self.state.crash_rounds.insert(&round_id, round)
+                    .expect("Failed to update crash round");
+
+                Ok(CasinoResponse::CrashCashedOut { round_id, multiplier })
+            }
+            CasinoOperation::EndCrashRound { round_id, seed } => {
+                self.check_house_operator()?;
+                let mut round = self.crash_round_in(round_id, CrashPhase::Running).await?;
+                if !randomness::verify_commit(&seed, &round.seed_hash) {
+                    return Err(CasinoError::InvalidRoundSeed);
+                }
+
+                let now = self.runtime.system_time();
+                let started_at = round.started_at.expect("Running crash rounds have started");
+                let entropy = randomness::round_entropy(&seed, round_id);
+                let round_params = CrashParams {
+                    house_edge_bps: round.house_edge_bps,
+                    ..CrashParams::default()
+                };
+                let crash_point = crash::Crash::calculate_outcome(ALGORITHM_VERSION, &entropy, &round_params)
+                    .expect("Current algorithm version must be supported");
+                // Until the curve reaches the crash point, bets can still cash out below it
+                if !crash::has_crashed(now.micros().saturating_sub(started_at.micros()), crash_point) {
+                    return Err(CasinoError::CrashRoundRunning);
+                }
+
+                round.phase = CrashPhase::Crashed;
+                round.crashed_at = Some(now);
+                round.seed = Some(seed);
+                round.crash_point = Some(crash_point);
+                let bets = round.bets.clone();
+                self.state.crash_rounds.insert(&round_id, round)
+                    .expect("Failed to update crash round");
+
+                for bet in bets {
+                    self.settle(Settlement {
+                        game_id: bet.game_id,
+                        player: bet.player,
+                        asset: bet.asset,
+                        bet_amount: bet.bet_amount,
+                        game_params: GameParams::Crash(bet.params),
+                        timestamp: bet.timestamp,
+                        liability: bet.liability,
+                        entropy,
+                        seeds: SeedRecord::CrashRound { round_id, seed },
+                    }).await;
+                }
+
+                Ok(CasinoResponse::CrashRoundEnded { round_id, crash_point })
+            }
+            CasinoOperation::ExpireCrashRound { round_id } => {
+                let mut round = self.crash_round(round_id).await?;
+                let Some(deadline) = round.deadline() else {
+                    return Err(CasinoError::WrongCrashPhase { phase: round.phase });
+                };
+                let now = self.runtime.system_time();
+                if now < deadline {
+                    return Err(CasinoError::RevealDeadlineNotReached { deadline });
+                }
+
+                // A round that never started returns its bets. Once running, withholding the
+                // seed can only be to dodge paying, so every bet is paid as if the curve never
+                // crashed.
+                let running = round.phase == CrashPhase::Running;
+                round.phase = CrashPhase::Expired;
+                round.crashed_at = Some(now);
+                let bets = round.bets.clone();
+                self.state.crash_rounds.insert(&round_id, round)
+                    .expect("Failed to update crash round");
+
+                for bet in bets {
+                    let payout = if running {
+                        let multiplier = crash::expired_multiplier(&bet.params);
+                        Amount::from_attos(bet.bet_amount.to_attos().saturating_mul(multiplier as u128) / 100)
+                    } else {
+                        bet.bet_amount
+                    };
+                    self.settle_at(bet.player, bet.asset, bet.bet_amount, bet.liability, payout).await;
+
+                    // Recorded without a seed, as there is no outcome to verify
+                    self.state.game_history.push(GameOutcome {
+                        game_id: bet.game_id,
+                        game_type: format!("{:?}", GameType::Crash),
+                        asset: bet.asset.to_string(),
+                        bet_amount: bet.bet_amount.to_string(),
+                        payout_amount: payout.to_string(),
+                        outcome_details: crash::expired_details(&bet.params, running),
+                        game_params: games::params_json(&GameParams::Crash(bet.params)),
+                        timestamp: bet.timestamp.micros(),
+                        player_seed: String::new(),
+                        house_seed: String::new(),
+                        house_epoch: 0,
+                        house_index: 0,
+                        client_seed: None,
+                        server_seed_hash: None,
+                        nonce: None,
+                        crash_round: Some(round_id),
+                        algorithm_version: ALGORITHM_VERSION,
+                        result: GameResult::default(),
+                    });
                 }
+
+                Ok(CasinoResponse::CrashRoundExpired { round_id })
+            }
+            CasinoOperation::RotateServerSeed { client_seed } => {
+                let player = self.signer()?;
+
+                let mut session = self.session(&player).await;
+                if session.server_chain.is_none() {
+                    return Err(CasinoError::NoServerSeed);
+                }
+                if session.open_rounds > 0 || session.pending_bets > 0 {
+                    return Err(CasinoError::GameInProgress);
+                }
+                if let Some(client_seed) = &client_seed {
+                    check_client_seed(client_seed)?;
+                }
+
+                // Every seed bound to a bet has been revealed with it; the rest of the chain is
+                // dropped. Without a pre-committed next chain, the house must commit one before
+                // the next bet.
+                session.server_chain = session.next_server_chain.take();
+                if let Some(client_seed) = client_seed {
+                    session.client_seed = client_seed;
+                }
+                self.state.sessions.insert(&player, session)
+                    .expect("Failed to update session");
+
+                Ok(CasinoResponse::SessionUpdated)
+            }
+            CasinoOperation::SetTableLimits { asset, limits } => {
+                self.check_house_operator()?;
+                self.check_asset_supported(asset)?;
+
+                match limits {
+                    Some(limits) => {
+                        if limits.min_bet > limits.max_bet || limits.max_exposure_bps > BASIS_POINTS {
+                            return Err(CasinoError::InvalidTableLimits);
+                        }
+                        self.state.tables.insert(&asset, limits)
+                            .expect("Failed to update table");
+                    }
+                    None => self.state.tables.remove(&asset)
+                        .expect("Failed to close table"),
+                }
+
+                Ok(CasinoResponse::TableUpdated)
+            }
+            CasinoOperation::SetHouseEdge { game_type, house_edge_bps } => {
+                self.check_house_operator()?;
+                if games::game(game_type).default_house_edge().is_none() {
+                    return Err(CasinoError::InvalidHouseEdge);
+                }
+
+                match house_edge_bps {
+                    Some(house_edge_bps) => {
+                        if house_edge_bps >= BASIS_POINTS {
+                            return Err(CasinoError::InvalidHouseEdge);
+                        }
+                        self.state.house_edges.insert(&game_type, house_edge_bps)
+                            .expect("Failed to update house edge");
+                    }
+                    None => self.state.house_edges.remove(&game_type)
+                        .expect("Failed to reset house edge"),
+                }
+
+                Ok(CasinoResponse::HouseEdgeUpdated)
+            }
+            CasinoOperation::FundBankroll { asset, amount } => {
+                let operator = self.check_house_operator()?;
+                self.check_asset_supported(asset)?;
+                self.table_limits(asset).await?;
+
+                self.receive_funds(asset, operator, amount);
+                let ledger = self.update_ledger(asset, |ledger| ledger.fund_house(amount.to_attos())).await;
+                self.assert_custody_covered(asset).await;
+
+                Ok(CasinoResponse::BankrollUpdated {
+                    bankroll: Amount::from_attos(ledger.house_equity),
+                })
+            }
+            CasinoOperation::WithdrawBankroll { asset, amount, destination } => {
+                self.check_house_operator()?;
+
+                // Funds locked for pending bets stay in the bankroll
+                let free_equity = self.ledger(asset).await.free_equity();
+                if free_equity < amount.to_attos() {
+                    return Err(CasinoError::InsufficientBankroll {
+                        available: Amount::from_attos(free_equity),
+                        required: amount,
+                    });
+                }
+                let ledger = self.update_ledger(asset, |ledger| ledger.withdraw_house(amount.to_attos())).await;
+
+                self.pay_out(asset, destination, amount);
+                self.assert_custody_covered(asset).await;
+
+                Ok(CasinoResponse::BankrollUpdated {
+                    bankroll: Amount::from_attos(ledger.house_equity),
+                })
             }
         }
     }
 
-    async fn execute_message(&mut self, _message: ()) {
-        panic!("Casino application doesn't support cross-chain messages");
+    /// The account owner holding the application's funds on this chain
+    fn custody_owner(&mut self) -> AccountOwner {
+        self.runtime.application_id().forget_abi().into()
     }
 
-    async fn store(mut self) {
-        self.state.save().await.expect("Failed to save state");
+    /// The owner who signed the block
+    fn signer(&mut self) -> Result<AccountOwner, CasinoError> {
+        self.runtime.authenticated_signer().ok_or(CasinoError::Unauthenticated)
+    }
+
+    /// Checks that the block is signed by the house operator, returning the operator
+    fn check_house_operator(&mut self) -> Result<AccountOwner, CasinoError> {
+        let signer = self.signer()?;
+        if *self.state.house_operator.get() != Some(signer) {
+            return Err(CasinoError::NotHouseOperator);
+        }
+        Ok(signer)
+    }
+
+    /// Checks that the asset is the native token or one of the accepted tokens
+    fn check_asset_supported(&mut self, asset: Asset) -> Result<(), CasinoError> {
+        match asset {
+            Asset::Token(token) if !self.runtime.application_parameters().tokens.contains(&token) => {
+                Err(CasinoError::UnsupportedAsset(asset))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Limits of the asset's table, if one is open
+    async fn table_limits(&self, asset: Asset) -> Result<TableLimits, CasinoError> {
+        self.state.tables.get(&asset)
+            .await
+            .expect("Failed to read table")
+            .ok_or(CasinoError::TableClosed(asset))
+    }
+
+    /// Checks that the bet fits the asset's table limits
+    async fn check_bet_within_limits(&self, asset: Asset, bet_amount: Amount) -> Result<(), CasinoError> {
+        let TableLimits { min_bet, max_bet, .. } = self.table_limits(asset).await?;
+        if bet_amount < min_bet || bet_amount > max_bet {
+            return Err(CasinoError::BetOutsideLimits { min_bet, max_bet });
+        }
+        Ok(())
+    }
+
+    /// A pending game by ID
+    async fn pending_game(&self, game_id: u64) -> Result<PendingGame, CasinoError> {
+        self.state.pending_games.get(&game_id)
+            .await
+            .expect("Failed to read pending game")
+            .ok_or(CasinoError::GameNotFound { game_id })
     }
+
+    /// A house seed chain bound to a pending game
+    async fn house_chain(&self, epoch: u32) -> HouseSeedChain {
+        self.state.house_chains.get(&epoch)
+            .await
+            .expect("Failed to read house chain")
+            .expect("Pending game refers to a missing house seed chain")
+    }
+
+    /// A session bet waiting for its server seed, by ID
+    async fn session_bet(&self, game_id: u64) -> Result<SessionBet, CasinoError> {
+        self.state.session_bets.get(&game_id)
+            .await
+            .expect("Failed to read session bet")
+            .ok_or(CasinoError::GameNotFound { game_id })
+    }
+
+    /// Settles a session bet whose server seed wasn't revealed in time at its highest payout,
+    /// and lets the session's later seeds be revealed past it
+    async fn time_out_session_bet(&mut self, game_id: u64, bet: SessionBet) -> Result<CasinoResponse, CasinoError> {
+        let deadline = reveal_deadline(bet.timestamp);
+        if self.runtime.system_time() < deadline {
+            return Err(CasinoError::RevealDeadlineNotReached { deadline });
+        }
+
+        let mut session = self.session(&bet.player).await;
+        session.server_chain.as_mut()
+            .expect("Sessions keep their chain while a bet is pending")
+            .timed_out.push(bet.nonce + 1);
+        if games::game_for(&bet.game_params).is_interactive() {
+            session.open_rounds -= 1;
+        } else {
+            session.pending_bets -= 1;
+        }
+        self.state.sessions.insert(&bet.player, session)
+            .expect("Failed to update session");
+        self.state.session_bets.remove(&game_id)
+            .expect("Failed to remove session bet");
+
+        Ok(self.settle_timed_out(game_id, bet.player, bet.asset, bet.bet_amount, bet.liability, true).await)
+    }
+
+    /// Settles a bet without its outcome: at its highest payout if it `won` by the other side
+    /// missing its deadline, otherwise at nothing
+    async fn settle_timed_out(
+        &mut self,
+        game_id: u64,
+        player: AccountOwner,
+        asset: Asset,
+        bet_amount: Amount,
+        liability: Amount,
+        won: bool,
+    ) -> CasinoResponse {
+        let payout = if won { bet_amount.saturating_add(liability) } else { Amount::ZERO };
+        self.settle_at(player, asset, bet_amount, liability, payout).await;
+
+        CasinoResponse::GameTimedOut { game_id, payout }
+    }
+
+    /// Settles a bet at a payout fixed without drawing its outcome
+    async fn settle_at(&mut self, player: AccountOwner, asset: Asset, bet_amount: Amount, liability: Amount, payout: Amount) {
+        let payout_attos = payout.to_attos();
+        self.update_ledger(asset, |ledger| ledger.settle_bet(bet_amount.to_attos(), liability.to_attos(), payout_attos)).await;
+        self.credit_player(player, asset, payout_attos).await;
+    }
+
+    /// A player's balance of one asset
+    async fn player_balance(&self, player: AccountOwner, asset: Asset) -> u128 {
+        match self.state.player_balances.try_load_entry(&player).await.expect("Failed to read balances") {
+            Some(balances) => balances.get(&asset)
+                .await
+                .expect("Failed to read balance")
+                .unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// Adds to a player's balance, returning the new balance
+    async fn credit_player(&mut self, player: AccountOwner, asset: Asset, amount: u128) -> u128 {
+        let new_balance = self.player_balance(player, asset).await.saturating_add(amount);
+        self.set_player_balance(player, asset, new_balance).await;
+        new_balance
+    }
+
+    /// Deducts from a player's balance, returning the new balance. Nothing is written if the
+    /// balance is short.
+    async fn debit_player(&mut self, player: AccountOwner, asset: Asset, amount: u128) -> Result<u128, CasinoError> {
+        let current_balance = self.player_balance(player, asset).await;
+        if current_balance < amount {
+            return Err(CasinoError::InsufficientBalance {
+                available: Amount::from_attos(current_balance),
+                required: Amount::from_attos(amount),
+            });
+        }
+        let new_balance = current_balance - amount;
+        self.set_player_balance(player, asset, new_balance).await;
+        Ok(new_balance)
+    }
+
+    async fn set_player_balance(&mut self, player: AccountOwner, asset: Asset, balance: u128) {
+        self.state.player_balances.load_entry_mut(&player)
+            .await
+            .expect("Failed to load balances")
+            .insert(&asset, balance)
+            .expect("Failed to update balance");
+    }
+
+    /// The ledger of an asset
+    async fn ledger(&self, asset: Asset) -> Ledger {
+        self.state.ledgers.get(&asset)
+            .await
+            .expect("Failed to read ledger")
+            .unwrap_or_default()
+    }
+
+    /// Applies `update` to the ledger of an asset, panicking if it leaves funds unaccounted for
+    async fn update_ledger(&mut self, asset: Asset, update: impl FnOnce(&mut Ledger)) -> Ledger {
+        let mut ledger = self.ledger(asset).await;
+        update(&mut ledger);
+        assert!(ledger.is_balanced(), "Ledger out of balance");
+        self.state.ledgers.insert(&asset, ledger)
+            .expect("Failed to update ledger");
+        ledger
+    }
+
+    /// The worst-case loss of a bet for the house, if it fits the table's share of the free
+    /// bankroll. The caller reserves it with [`Ledger::lock_bet`].
+    async fn bet_liability(
+        &self,
+        asset: Asset,
+        bet_amount: Amount,
+        game_params: &GameParams,
+    ) -> Result<Amount, CasinoError> {
+        let bet_attos = bet_amount.to_attos();
+        // Saturating, so a huge bet on a huge multiplier is rejected instead of wrapping around
+        let max_payout = bet_attos.saturating_mul(games::game_for(game_params).max_multiplier(game_params) as u128) / 100;
+        // The stake is already in custody, so the house only risks the winnings on top of it
+        let liability = Amount::from_attos(max_payout.saturating_sub(bet_attos));
+        self.check_exposure(asset, liability).await?;
+        Ok(liability)
+    }
+
+    /// Checks that a liability fits the table's share of the free bankroll
+    async fn check_exposure(&self, asset: Asset, liability: Amount) -> Result<(), CasinoError> {
+        let limits = self.table_limits(asset).await?;
+        let free_equity = self.ledger(asset).await.free_equity();
+        let max_exposure = free_equity.saturating_mul(limits.max_exposure_bps as u128) / BASIS_POINTS as u128;
+        if liability.to_attos() > max_exposure {
+            return Err(CasinoError::ExposureLimitExceeded {
+                liability,
+                max_exposure: Amount::from_attos(max_exposure),
+            });
+        }
+        Ok(())
+    }
+
+    /// Transfers tokens from `source` into the application's custody; callers record them in the ledger
+    fn receive_funds(&mut self, asset: Asset, source: AccountOwner, amount: Amount) {
+        let custody = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: self.custody_owner(),
+        };
+        match asset {
+            Asset::Native => self.runtime.transfer(source, custody, amount),
+            Asset::Token(token) => {
+                // Forward the signer's authentication so the token lets us debit their account
+                let transfer = FungibleOperation::Transfer {
+                    owner: source,
+                    amount,
+                    target_account: custody,
+                };
+                self.runtime.call_application(true, token.with_abi::<FungibleTokenAbi>(), &transfer);
+            }
+        }
+    }
+
+    /// Transfers tokens out of the application's custody to `destination`; callers record it in the ledger
+    fn pay_out(&mut self, asset: Asset, destination: Account, amount: Amount) {
+        let custody = self.custody_owner();
+        match asset {
+            Asset::Native => self.runtime.transfer(custody, destination, amount),
+            Asset::Token(token) => {
+                // The token authorizes debiting the custody account because we are its caller
+                let transfer = FungibleOperation::Transfer {
+                    owner: custody,
+                    amount,
+                    target_account: destination,
+                };
+                self.runtime.call_application(true, token.with_abi::<FungibleTokenAbi>(), &transfer);
+            }
+        }
+    }
+
+    /// The application's actual balance of an asset
+    fn custody_balance(&mut self, asset: Asset) -> Amount {
+        let custody = self.custody_owner();
+        match asset {
+            Asset::Native => self.runtime.owner_balance(custody),
+            Asset::Token(token) => {
+                let query = FungibleOperation::Balance { owner: custody };
+                match self.runtime.call_application(false, token.with_abi::<FungibleTokenAbi>(), &query) {
+                    FungibleResponse::Balance(balance) => balance,
+                    response => panic!("Unexpected response from token application: {:?}", response),
+                }
+            }
+        }
+    }
+
+    /// Panics if the application's on-chain balance doesn't cover the recorded total funds.
+    ///
+    /// Anyone can transfer tokens to the application's account directly, so the balance may
+    /// exceed `total_funds`; it must never fall below it.
+    async fn assert_custody_covered(&mut self, asset: Asset) {
+        let on_chain = self.custody_balance(asset);
+        assert!(
+            on_chain.to_attos() >= self.ledger(asset).await.total_funds,
+            "Casino custody balance does not cover total funds"
+        );
+    }
+
+    /// Settles the game once both seeds are revealed, otherwise stores the partial reveal
+    async fn settle_or_store(&mut self, game_id: u64, pending_game: PendingGame) -> CasinoResponse {
+        let (Some(player_seed), Some(house_seed)) = (pending_game.player_seed, pending_game.house_seed) else {
+            self.state.pending_games.insert(&game_id, pending_game)
+                .expect("Failed to update pending game");
+            return CasinoResponse::RevealAccepted { game_id };
+        };
+
+        // Remove pending game
+        self.state.pending_games.remove(&game_id)
+            .expect("Failed to remove pending game");
+
+        self.settle(Settlement {
+            game_id,
+            player: pending_game.player,
+            asset: pending_game.asset,
+            bet_amount: pending_game.bet_amount,
+            game_params: pending_game.game_params,
+            timestamp: pending_game.timestamp,
+            liability: pending_game.liability,
+            entropy: randomness::combine_seeds(&player_seed, &house_seed, game_id),
+            seeds: SeedRecord::CommitReveal {
+                player_seed,
+                house_seed,
+                house_epoch: pending_game.house_epoch,
+                house_index: pending_game.house_index,
+            },
+        }).await
+    }
+
+    /// Sets the house edge the bet's game is configured with in its params, for games whose
+    /// payouts depend on one. Players can't pick their own edge: whatever they sent is replaced.
+    async fn bind_house_edge(&self, game_params: &mut GameParams) {
+        let game = games::game_for(game_params);
+        if let Some(house_edge_bps) = self.house_edge(game.game_type()).await {
+            game.set_house_edge(game_params, house_edge_bps);
+        }
+    }
+
+    /// House edge new bets on a game are placed at, if its payouts depend on one
+    async fn house_edge(&self, game_type: GameType) -> Option<u32> {
+        let default_edge = games::game(game_type).default_house_edge()?;
+        let house_edge_bps = self.state.house_edges.get(&game_type)
+            .await
+            .expect("Failed to read house edge")
+            .unwrap_or(default_edge);
+        Some(house_edge_bps)
+    }
+
+    /// Loads a crash round
+    async fn crash_round(&self, round_id: u64) -> Result<CrashRound, CasinoError> {
+        self.state.crash_rounds.get(&round_id)
+            .await
+            .expect("Failed to read crash round")
+            .ok_or(CasinoError::CrashRoundNotFound { round_id })
+    }
+
+    /// Loads a crash round, if it is in `phase`
+    async fn crash_round_in(&self, round_id: u64, phase: CrashPhase) -> Result<CrashRound, CasinoError> {
+        let round = self.crash_round(round_id).await?;
+        if round.phase != phase {
+            return Err(CasinoError::WrongCrashPhase { phase: round.phase });
+        }
+        Ok(round)
+    }
+
+    /// Loads a Mines round in play, if the player is the one who bet on it
+    async fn mines_round(&self, game_id: u64, player: AccountOwner) -> Result<MinesRound, CasinoError> {
+        let round = self.state.mines_rounds.get(&game_id)
+            .await
+            .expect("Failed to read mines round")
+            .ok_or(CasinoError::GameNotFound { game_id })?;
+        if round.player != player {
+            return Err(CasinoError::NotGameOwner);
+        }
+        Ok(round)
+    }
+
+    /// Settles a finished Mines round once its server seed is revealed, checking the seed
+    /// deals the board the house committed to
+    async fn end_mines_round(&mut self, game_id: u64, round: MinesRound, server_seed: [u8; 32]) -> Result<CasinoResponse, CasinoError> {
+        if !round.finished {
+            return Err(CasinoError::RoundNotFinished);
+        }
+        let mut session = self.session(&round.player).await;
+        session.server_chain.as_mut()
+            .expect("Sessions keep their chain while a round is in play")
+            .reveal(round.nonce + 1, server_seed)?;
+
+        let entropy = randomness::session_entropy(&server_seed, &round.client_seed, round.nonce);
+        let mine_positions = Mines::calculate_outcome(ALGORITHM_VERSION, &entropy, &round.params)
+            .expect("Current algorithm version must be supported");
+        // A house that committed to another board can't settle, and the round times out in
+        // the player's favor
+        if commitment::root(&mines::board(&round.params, &mine_positions), &entropy) != round.board_root {
+            return Err(CasinoError::CommitmentMismatch);
+        }
+
+        session.open_rounds -= 1;
+        self.state.sessions.insert(&round.player, session)
+            .expect("Failed to update session");
+        self.state.mines_rounds.remove(&game_id)
+            .expect("Failed to remove mines round");
+
+        Ok(self.settle(Settlement {
+            game_id,
+            player: round.player,
+            asset: round.asset,
+            bet_amount: round.bet_amount,
+            game_params: GameParams::Mines(round.params),
+            timestamp: round.timestamp,
+            liability: round.liability,
+            entropy,
+            seeds: SeedRecord::Session {
+                client_seed: round.client_seed,
+                server_seed_hash: round.server_seed_hash,
+                nonce: round.nonce,
+                server_seed,
+            },
+        }).await)
+    }
+
+    /// Checks the house left a round waiting past the reveal deadline, and closes it in the
+    /// session, letting later server seeds be revealed past it
+    async fn time_out_round(
+        &mut self,
+        player: AccountOwner,
+        nonce: u64,
+        awaiting_house_since: Timestamp,
+    ) -> Result<(), CasinoError> {
+        let deadline = reveal_deadline(awaiting_house_since);
+        if self.runtime.system_time() < deadline {
+            return Err(CasinoError::RevealDeadlineNotReached { deadline });
+        }
+
+        let mut session = self.session(&player).await;
+        session.server_chain.as_mut()
+            .expect("Sessions keep their chain while a round is in play")
+            .timed_out.push(nonce + 1);
+        session.open_rounds -= 1;
+        self.state.sessions.insert(&player, session)
+            .expect("Failed to update session");
+        Ok(())
+    }
+
+    /// Loads a Blackjack hand in play, if the player is the one who bet on it
+    async fn blackjack_round(&self, game_id: u64, player: AccountOwner) -> Result<BlackjackRound, CasinoError> {
+        let round = self.state.blackjack_rounds.get(&game_id)
+            .await
+            .expect("Failed to read blackjack round")
+            .ok_or(CasinoError::GameNotFound { game_id })?;
+        if round.player != player {
+            return Err(CasinoError::NotGameOwner);
+        }
+        Ok(round)
+    }
+
+    /// Deals a Blackjack hand by the root of its shoe; the openings show the player's cards,
+    /// the dealer's up card and the dealer's peek when a ten or an ace is up
+    async fn deal_blackjack_round(
+        &mut self,
+        game_id: u64,
+        bet: SessionBet,
+        params: BlackjackParams,
+        root: [u8; 32],
+        openings: Vec<Opening>,
+    ) -> Result<CasinoResponse, CasinoError> {
+        let mut cards = BTreeMap::new();
+        for opening in &openings {
+            let dealt = (opening.position as usize) < blackjack::HOLE_CARD || opening.position as usize == blackjack::PEEK_POSITION;
+            if !dealt || !blackjack::is_valid_opening(opening.position, opening.value) || !commitment::verify(&root, opening) {
+                return Err(CasinoError::InvalidOpening);
+            }
+            cards.insert(opening.position, opening.value);
+        }
+        let table = blackjack::play_opened(&cards, &params).expect("No actions are taken before the deal");
+        if table.waiting.is_some() && !table.player_done() {
+            return Err(CasinoError::InvalidOpening);
+        }
+
+        let now = self.runtime.system_time();
+        let round = BlackjackRound {
+            player: bet.player,
+            asset: bet.asset,
+            staked: bet.bet_amount,
+            timestamp: bet.timestamp,
+            liability: bet.liability,
+            params,
+            shoe_root: root,
+            cards,
+            // A blackjack on either side ends the hand on the deal
+            awaiting_house_since: table.player_done().then_some(now),
+            last_action_at: now,
+            auto_stand: false,
+            client_seed: bet.client_seed,
+            server_seed_hash: bet.server_seed_hash,
+            nonce: bet.nonce,
+        };
+        let response = if round.awaiting_house_since.is_some() {
+            CasinoResponse::AwaitingHouse { game_id }
+        } else {
+            CasinoResponse::RoundDealt { game_id }
+        };
+        self.state.session_bets.remove(&game_id)
+            .expect("Failed to remove session bet");
+        self.state.blackjack_rounds.insert(&game_id, round)
+            .expect("Failed to insert blackjack round");
+
+        Ok(response)
+    }
+
+    /// Opens the cards a Blackjack hand waits for, one position after the other
+    async fn answer_blackjack_round(
+        &mut self,
+        game_id: u64,
+        mut round: BlackjackRound,
+        openings: Vec<Opening>,
+    ) -> Result<CasinoResponse, CasinoError> {
+        if openings.is_empty() {
+            return Err(CasinoError::InvalidOpening);
+        }
+        for opening in &openings {
+            let table = blackjack::play_opened(&round.cards, &round.params).expect("Hands in play replay");
+            let waiting = table.waiting.ok_or(CasinoError::AwaitingPlayer)?;
+            if opening.position as usize != waiting
+                || !blackjack::is_valid_opening(opening.position, opening.value)
+                || !commitment::verify(&round.shoe_root, opening)
+            {
+                return Err(CasinoError::InvalidOpening);
+            }
+            round.cards.insert(opening.position, opening.value);
+        }
+
+        // The hand waits for the player again, or for the server seed once the player is done.
+        // A player who timed out stands on the cards just opened, which may draw another.
+        let actions = round.params.actions.len();
+        let table = if round.auto_stand {
+            blackjack::stand_opened(&round.cards, &mut round.params)
+        } else {
+            blackjack::play_opened(&round.cards, &round.params)
+        }.expect("Hands in play replay");
+        round.last_action_at = self.runtime.system_time();
+        if table.player_done() || round.params.actions.len() > actions {
+            round.awaiting_house_since = Some(round.last_action_at);
+        } else if table.waiting.is_none() {
+            round.awaiting_house_since = None;
+        }
+        let response = if round.awaiting_house_since.is_some() {
+            CasinoResponse::AwaitingHouse { game_id }
+        } else {
+            CasinoResponse::BlackjackHandUpdated { game_id }
+        };
+        self.state.blackjack_rounds.insert(&game_id, round)
+            .expect("Failed to update blackjack round");
+
+        Ok(response)
+    }
+
+    /// Settles a Blackjack hand the player is done with on everything staked, once its server
+    /// seed is revealed and shuffles the shoe the house committed to
+    async fn end_blackjack_round(&mut self, game_id: u64, round: BlackjackRound, server_seed: [u8; 32]) -> Result<CasinoResponse, CasinoError> {
+        let table = blackjack::play_opened(&round.cards, &round.params).expect("Hands in play replay");
+        if !table.player_done() {
+            return Err(CasinoError::RoundNotFinished);
+        }
+        let mut session = self.session(&round.player).await;
+        session.server_chain.as_mut()
+            .expect("Sessions keep their chain while a round is in play")
+            .reveal(round.nonce + 1, server_seed)?;
+
+        let entropy = randomness::session_entropy(&server_seed, &round.client_seed, round.nonce);
+        let shoe = blackjack::shoe(&entropy);
+        // A house that committed to another shoe can't settle, and the hand times out in the
+        // player's favor
+        if commitment::root(&blackjack::committed_values(&shoe), &entropy) != round.shoe_root {
+            return Err(CasinoError::CommitmentMismatch);
+        }
+
+        session.open_rounds -= 1;
+        self.state.sessions.insert(&round.player, session)
+            .expect("Failed to update session");
+        self.state.blackjack_rounds.remove(&game_id)
+            .expect("Failed to remove blackjack round");
+
+        Ok(self.settle(Settlement {
+            game_id,
+            player: round.player,
+            asset: round.asset,
+            bet_amount: round.staked,
+            game_params: GameParams::Blackjack(round.params),
+            timestamp: round.timestamp,
+            liability: round.liability,
+            entropy,
+            seeds: SeedRecord::Session {
+                client_seed: round.client_seed,
+                server_seed_hash: round.server_seed_hash,
+                nonce: round.nonce,
+                server_seed,
+            },
+        }).await)
+    }
+
+    /// Computes the outcome of a bet, credits the payout and records it in the history
+    async fn settle(&mut self, settlement: Settlement) -> CasinoResponse {
+        let Settlement { game_id, player, asset, bet_amount, game_params, timestamp, liability, entropy, seeds } = settlement;
+
+        // Calculate game outcome with the bet's game, using the current sampling algorithm
+        let game = games::game_for(&game_params);
+        let settled = game.calculate_outcome(
+            ALGORITHM_VERSION,
+            &entropy,
+            &game_params,
+        ).expect("Current algorithm version must be supported");
+
+        // Calculate payout (multiplier is percentage, e.g., 200 = 2x, or the sum of the
+        // payouts of each position the bet was split between)
+        let bet_attos = bet_amount.to_attos();
+        let payout = settled.payout(bet_amount);
+        let payout_attos = payout.to_attos();
+
+        // Release the bet's reserved liability, then the house keeps the bet and pays the
+        // payout out of its bankroll
+        self.update_ledger(asset, |ledger| ledger.settle_bet(bet_attos, liability.to_attos(), payout_attos)).await;
+
+        // Credit payout to player balance
+        self.credit_player(player, asset, payout_attos).await;
+
+        // Record game outcome
+        let mut game_outcome = GameOutcome {
+            game_id,
+            game_type: format!("{:?}", game.game_type()),
+            asset: asset.to_string(),
+            bet_amount: bet_amount.to_string(),
+            payout_amount: payout.to_string(),
+            game_params: games::params_json(&game_params),
+            outcome_details: settled.details.clone(),
+            timestamp: timestamp.micros(),
+            player_seed: String::new(),
+            house_seed: String::new(),
+            house_epoch: 0,
+            house_index: 0,
+            client_seed: None,
+            server_seed_hash: None,
+            nonce: None,
+            crash_round: None,
+            algorithm_version: ALGORITHM_VERSION,
+            result: settled.result,
+        };
+        match seeds {
+            SeedRecord::CommitReveal { player_seed, house_seed, house_epoch, house_index } => {
+                game_outcome.player_seed = hex::encode(player_seed);
+                game_outcome.house_seed = hex::encode(house_seed);
+                game_outcome.house_epoch = house_epoch;
+                game_outcome.house_index = house_index;
+            }
+            SeedRecord::Session { client_seed, server_seed_hash, nonce, server_seed } => {
+                game_outcome.house_seed = hex::encode(server_seed);
+                game_outcome.client_seed = Some(client_seed);
+                game_outcome.server_seed_hash = Some(hex::encode(server_seed_hash));
+                game_outcome.nonce = Some(nonce);
+            }
+            SeedRecord::CrashRound { round_id, seed } => {
+                game_outcome.house_seed = hex::encode(seed);
+                game_outcome.crash_round = Some(round_id);
+            }
+        }
+        self.state.game_history.push(game_outcome);
+
+        CasinoResponse::GameCompleted {
+            game_id,
+            outcome: settled.details,
+            payout,
+        }
+    }
+
+    /// Loads a player's session, or an empty one if they have none yet
+    async fn session(&self, player: &AccountOwner) -> PlayerSession {
+        self.state.sessions.get(player)
+            .await
+            .expect("Failed to read session")
+            .unwrap_or_default()
+    }
+}
+
+/// Checks that the client seed is a non-empty string of at most 64 characters
+fn check_client_seed(client_seed: &str) -> Result<(), CasinoError> {
+    if client_seed.is_empty() || client_seed.chars().count() > 64 {
+        return Err(CasinoError::InvalidClientSeed);
+    }
+    Ok(())
+}
+
+/// A bet whose entropy is known and can be settled
+struct Settlement {
+    game_id: u64,
+    player: AccountOwner,
+    asset: Asset,
+    bet_amount: Amount,
+    game_params: GameParams,
+    timestamp: Timestamp,
+    /// Liability locked for the bet when it was placed
+    liability: Amount,
+    entropy: [u8; 32],
+    seeds: SeedRecord,
+}
+
+/// The seeds a settled game was derived from, recorded in its outcome for verification
+enum SeedRecord {
+    CommitReveal {
+        player_seed: [u8; 32],
+        house_seed: [u8; 32],
+        house_epoch: u32,
+        house_index: u64,
+    },
+    Session {
+        client_seed: String,
+        server_seed_hash: [u8; 32],
+        nonce: u64,
+        server_seed: [u8; 32],
+    },
+    CrashRound {
+        round_id: u64,
+        seed: [u8; 32],
+    },
 }
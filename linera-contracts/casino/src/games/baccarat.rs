@@ -0,0 +1,276 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Baccarat game logic
+
+use std::cmp::Ordering;
+
+use linera_sdk::linera_base_types::Amount;
+
+use super::{entropy::EntropyStream, Game};
+use crate::{BaccaratBet, BaccaratParams, BaccaratResult, GameParams, GameResult, GameType, PlayingCard};
+
+/// Decks in the shoe
+pub const DECKS: usize = 8;
+
+/// A winning banker bet pays even money less 5% commission
+const BANKER_MULTIPLIER: u32 = 195;
+
+/// A tie pays 8:1
+const TIE_MULTIPLIER: u32 = 900;
+
+/// A pair side bet pays 11:1
+const PAIR_MULTIPLIER: u32 = 1200;
+
+/// A single coup dealt from a freshly shuffled shoe, drawing third cards by the standard
+/// tableau. The main bet can come with pair side bets, in which case every stake is given as
+/// an amount, like roulette chips.
+pub struct Baccarat;
+
+impl Game for Baccarat {
+    const GAME_TYPE: GameType = GameType::Baccarat;
+    type Params = BaccaratParams;
+    type Outcome = Coup;
+
+    fn params(game_params: &GameParams) -> Option<&BaccaratParams> {
+        match game_params {
+            GameParams::Baccarat(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that side bets come with the main bet's amount and that every amount stakes something
+    fn validate(params: &BaccaratParams) -> Result<(), String> {
+        if params.amount.is_none() && (params.player_pair.is_some() || params.banker_pair.is_some()) {
+            return Err("Side bets need the amount of the main bet".to_string());
+        }
+        for (name, amount) in stakes(params) {
+            if amount == Amount::ZERO {
+                return Err(format!("The {} bet stakes nothing", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// The main bet and the side bets, once they are given as amounts
+    fn staked(params: &BaccaratParams) -> Option<Amount> {
+        params.amount?;
+        Some(stakes(params).iter().fold(Amount::ZERO, |total, (_, amount)| total.saturating_add(*amount)))
+    }
+
+    /// Highest multiplier of the whole stake, rounded up so the liability reserved for side bets
+    /// covers what they can pay
+    fn max_multiplier(params: &BaccaratParams) -> u32 {
+        let staked = staked_attos(params);
+        let mut most = 0;
+        for winner in [BaccaratBet::Player, BaccaratBet::Banker, BaccaratBet::Tie] {
+            if staked == 0 {
+                most = most.max(bet_multiplier(params.bet, winner));
+                continue;
+            }
+            for (player_pair, banker_pair) in [(false, false), (true, false), (false, true), (true, true)] {
+                let returned = returned(params, winner, player_pair, banker_pair);
+                most = most.max(returned.div_ceil(staked) as u32);
+            }
+        }
+        most
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], _params: &BaccaratParams) -> Option<Coup> {
+        match algorithm_version {
+            4 => Some(deal(&shoe(entropy))),
+            _ => None,
+        }
+    }
+
+    fn multiplier(params: &BaccaratParams, coup: &Coup) -> u32 {
+        let staked = staked_attos(params);
+        if staked == 0 {
+            return bet_multiplier(params.bet, coup.winner());
+        }
+        (returned(params, coup.winner(), coup.player_pair(), coup.banker_pair()) / staked) as u32
+    }
+
+    /// Each stake is paid on its own amount
+    fn payout(params: &BaccaratParams, coup: &Coup) -> Option<Amount> {
+        params.amount?;
+        Some(Amount::from_attos(returned(params, coup.winner(), coup.player_pair(), coup.banker_pair()) / 100))
+    }
+
+    fn serialize_outcome(params: &BaccaratParams, coup: &Coup) -> String {
+        let mut bets = bet_label(params.bet).to_string();
+        if params.player_pair.is_some() {
+            bets.push_str(" + player pair");
+        }
+        if params.banker_pair.is_some() {
+            bets.push_str(" + banker pair");
+        }
+        format!(
+            "Baccarat: Player {} = {}, Banker {} = {}, {}, Bet: {}",
+            cards_label(&coup.player),
+            hand_total(&coup.player),
+            cards_label(&coup.banker),
+            hand_total(&coup.banker),
+            match coup.winner() {
+                BaccaratBet::Player => "Player wins",
+                BaccaratBet::Banker => "Banker wins",
+                BaccaratBet::Tie => "Tie",
+            },
+            bets
+        )
+    }
+
+    fn result(_params: &BaccaratParams, coup: &Coup) -> GameResult {
+        GameResult {
+            baccarat: Some(BaccaratResult {
+                player_cards: coup.player.iter().map(|&card| PlayingCard::from_index(card)).collect(),
+                banker_cards: coup.banker.iter().map(|&card| PlayingCard::from_index(card)).collect(),
+                player_total: hand_total(&coup.player),
+                banker_total: hand_total(&coup.banker),
+                winner: coup.winner(),
+                player_pair: coup.player_pair(),
+                banker_pair: coup.banker_pair(),
+            }),
+            ..GameResult::default()
+        }
+    }
+
+    /// Baccarat was added after params were typed
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        None
+    }
+}
+
+/// The cards of a coup, as card indices 0 to 51 in the order each side drew them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coup {
+    pub player: Vec<u8>,
+    pub banker: Vec<u8>,
+}
+
+impl Coup {
+    pub fn winner(&self) -> BaccaratBet {
+        match hand_total(&self.player).cmp(&hand_total(&self.banker)) {
+            Ordering::Greater => BaccaratBet::Player,
+            Ordering::Less => BaccaratBet::Banker,
+            Ordering::Equal => BaccaratBet::Tie,
+        }
+    }
+
+    /// Whether the player's first two cards have the same rank
+    pub fn player_pair(&self) -> bool {
+        self.player[0] % 13 == self.player[1] % 13
+    }
+
+    /// Whether the banker's first two cards have the same rank
+    pub fn banker_pair(&self) -> bool {
+        self.banker[0] % 13 == self.banker[1] % 13
+    }
+}
+
+/// A shoe of [`DECKS`] decks shuffled from the entropy, as card indices 0 to 51
+pub fn shoe(entropy: &[u8; 32]) -> Vec<u8> {
+    let mut shoe: Vec<u8> = (0..52 * DECKS).map(|index| (index % 52) as u8).collect();
+    EntropyStream::new(entropy, b"baccarat").shuffle(&mut shoe);
+    shoe
+}
+
+/// Deals a coup from the top of the shoe: player, banker, player, banker, then third cards by
+/// the tableau. A natural 8 or 9 on either side stands both; otherwise the player draws on 0 to
+/// 5, and the banker draws on what it holds and the player's third card.
+pub fn deal(shoe: &[u8]) -> Coup {
+    let mut cards = shoe.iter().copied();
+    let mut draw = || cards.next().expect("The shoe holds every card a coup needs");
+    let mut coup = Coup { player: vec![draw()], banker: vec![draw()] };
+    coup.player.push(draw());
+    coup.banker.push(draw());
+
+    let (player, banker) = (hand_total(&coup.player), hand_total(&coup.banker));
+    if player >= 8 || banker >= 8 {
+        return coup;
+    }
+    let player_third = if player <= 5 {
+        let card = draw();
+        coup.player.push(card);
+        Some(card_value(card))
+    } else {
+        None
+    };
+    let banker_draws = match player_third {
+        None => banker <= 5,
+        Some(third) => match banker {
+            0..=2 => true,
+            3 => third != 8,
+            4 => (2..=7).contains(&third),
+            5 => (4..=7).contains(&third),
+            6 => (6..=7).contains(&third),
+            _ => false,
+        },
+    };
+    if banker_draws {
+        coup.banker.push(draw());
+    }
+    coup
+}
+
+/// Baccarat value of a card: aces count 1, tens and faces 0
+fn card_value(card: u8) -> u8 {
+    match card % 13 {
+        rank @ 0..=8 => rank + 1,
+        _ => 0,
+    }
+}
+
+/// Total of a hand, the last digit of its card values' sum
+pub fn hand_total(cards: &[u8]) -> u8 {
+    cards.iter().map(|&card| card_value(card)).sum::<u8>() % 10
+}
+
+/// Multiplier (* 100) of the main bet once `winner` is known; ties push bets on either side
+fn bet_multiplier(bet: BaccaratBet, winner: BaccaratBet) -> u32 {
+    match (bet, winner) {
+        (BaccaratBet::Player, BaccaratBet::Player) => 200,
+        (BaccaratBet::Banker, BaccaratBet::Banker) => BANKER_MULTIPLIER,
+        (BaccaratBet::Tie, BaccaratBet::Tie) => TIE_MULTIPLIER,
+        (BaccaratBet::Player | BaccaratBet::Banker, BaccaratBet::Tie) => 100,
+        _ => 0,
+    }
+}
+
+/// The main bet and the side bets placed with it, by name, when given as amounts
+fn stakes(params: &BaccaratParams) -> Vec<(&'static str, Amount)> {
+    [
+        (bet_label(params.bet), params.amount),
+        ("player pair", params.player_pair),
+        ("banker pair", params.banker_pair),
+    ]
+    .into_iter()
+    .filter_map(|(name, amount)| Some((name, amount?)))
+    .collect()
+}
+
+fn staked_attos(params: &BaccaratParams) -> u128 {
+    if params.amount.is_none() {
+        return 0;
+    }
+    stakes(params).iter().map(|(_, amount)| amount.to_attos()).fold(0, u128::saturating_add)
+}
+
+/// Sum of each stake times its multiplier (* 100), in attos
+fn returned(params: &BaccaratParams, winner: BaccaratBet, player_pair: bool, banker_pair: bool) -> u128 {
+    let attos = |amount: Option<Amount>| amount.map_or(0, |amount| amount.to_attos());
+    let pair = |paired: bool| if paired { PAIR_MULTIPLIER as u128 } else { 0 };
+    attos(params.amount).saturating_mul(bet_multiplier(params.bet, winner) as u128)
+        .saturating_add(attos(params.player_pair).saturating_mul(pair(player_pair)))
+        .saturating_add(attos(params.banker_pair).saturating_mul(pair(banker_pair)))
+}
+
+fn bet_label(bet: BaccaratBet) -> &'static str {
+    match bet {
+        BaccaratBet::Player => "player",
+        BaccaratBet::Banker => "banker",
+        BaccaratBet::Tie => "tie",
+    }
+}
+
+fn cards_label(cards: &[u8]) -> String {
+    cards.iter().map(|&card| PlayingCard::from_index(card).to_string()).collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,536 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Blackjack game logic
+
+use std::collections::BTreeMap;
+
+use linera_sdk::linera_base_types::Amount;
+
+use super::{entropy::EntropyStream, Game};
+use crate::{
+    BlackjackAction, BlackjackHandResult, BlackjackParams, BlackjackPayout, BlackjackResult, GameParams, GameResult,
+    GameType, PlayingCard,
+};
+
+/// Decks in the shoe
+pub const DECKS: usize = 6;
+
+/// Hands a player can split into
+pub const MAX_HANDS: usize = 4;
+
+/// Position of the dealer's hole card in the shoe
+pub const HOLE_CARD: usize = 3;
+
+/// Position of the value the house commits to after the shoe's cards: 1 if the dealer's up
+/// and hole cards are a blackjack, which the dealer peeks for
+pub const PEEK_POSITION: usize = 52 * DECKS;
+
+/// Stands in for the hole card while the hand is played from opened cards
+const HIDDEN_CARD: u8 = 0;
+
+/// Blackjack is played action by action: a session bet shuffles the shoe and the house
+/// commits to it, opening cards as the player hits, stands, doubles or splits each hand. The
+/// dealer plays once the server seed is revealed. The recorded params list the actions in
+/// order, so a finished hand replays like any other game.
+///
+/// Stakes and payouts are counted in hundredths of the initial bet: doubling or splitting
+/// stakes 100 more, insurance 50.
+pub struct Blackjack;
+
+impl Game for Blackjack {
+    const GAME_TYPE: GameType = GameType::Blackjack;
+    const INTERACTIVE: bool = true;
+    type Params = BlackjackParams;
+    type Outcome = Table;
+
+    fn params(game_params: &GameParams) -> Option<&BlackjackParams> {
+        match game_params {
+            GameParams::Blackjack(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that no action is taken before the cards are dealt
+    fn validate(params: &BlackjackParams) -> Result<(), String> {
+        if !params.actions.is_empty() {
+            return Err("Actions are taken one at a time once the cards are dealt".to_string());
+        }
+        Ok(())
+    }
+
+    /// A blackjack on the initial bet; doubles, splits and insurance stake and lock more
+    /// as they are played
+    fn max_multiplier(params: &BlackjackParams) -> u32 {
+        100 + blackjack_bonus(params)
+    }
+
+    /// The table after the recorded actions, with the hands still in play standing
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &BlackjackParams) -> Option<Table> {
+        match algorithm_version {
+            4 => {
+                let shoe = shoe(entropy);
+                let mut table = play(&shoe, params).ok()?;
+                table.stand_all(shoe.as_slice(), params);
+                Some(table)
+            }
+            _ => None,
+        }
+    }
+
+    /// What every hand and the insurance return, in hundredths of the initial bet
+    fn multiplier(params: &BlackjackParams, table: &Table) -> u32 {
+        let hands: u32 = (0..table.hands.len()).map(|index| table.hand_multiplier(params, index)).sum();
+        hands + table.insurance_multiplier()
+    }
+
+    /// Payouts are counted on the initial bet, while the bet amount records everything staked
+    fn payout(params: &BlackjackParams, table: &Table) -> Option<Amount> {
+        Some(Amount::from_attos(params.stake.to_attos() * Self::multiplier(params, table) as u128 / 100))
+    }
+
+    fn serialize_outcome(params: &BlackjackParams, table: &Table) -> String {
+        let mut details = format!(
+            "Blackjack: Dealer {} = {}",
+            cards_label(&table.dealer),
+            hand_total(&table.dealer).0
+        );
+        for (index, hand) in table.hands.iter().enumerate() {
+            details.push_str(&format!(
+                "; Hand {} {} = {}{} {}",
+                index + 1,
+                cards_label(&hand.cards),
+                hand_total(&hand.cards).0,
+                if hand.doubled { " doubled" } else { "" },
+                multiplier_label(table.hand_multiplier(params, index))
+            ));
+        }
+        if table.insurance == Some(true) {
+            details.push_str(&format!("; Insurance {}", multiplier_label(table.insurance_multiplier())));
+        }
+        details
+    }
+
+    fn result(params: &BlackjackParams, table: &Table) -> GameResult {
+        let hands = table.hands.iter()
+            .enumerate()
+            .map(|(index, hand)| BlackjackHandResult {
+                cards: hand.cards.iter().map(|&card| PlayingCard::from_index(card)).collect(),
+                total: hand_total(&hand.cards).0,
+                doubled: hand.doubled,
+                multiplier: table.hand_multiplier(params, index),
+            })
+            .collect();
+        GameResult {
+            blackjack: Some(BlackjackResult {
+                hands,
+                dealer: table.dealer.iter().map(|&card| PlayingCard::from_index(card)).collect(),
+                dealer_total: hand_total(&table.dealer).0,
+                insurance: table.insurance == Some(true),
+                actions: params.actions.clone(),
+            }),
+            ..GameResult::default()
+        }
+    }
+
+    /// Blackjack was added after params were typed
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        None
+    }
+}
+
+/// A player's hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hand {
+    /// Cards of the shoe (0 to 51), in the order they were dealt
+    pub cards: Vec<u8>,
+    pub doubled: bool,
+    /// Split aces get a single card each
+    pub split_aces: bool,
+    /// Whether the player is done with the hand
+    pub done: bool,
+}
+
+/// The shoe as far as the table can see it
+pub trait Cards {
+    /// The card at a position, `None` while it is hidden
+    fn card(&self, position: usize) -> Option<u8>;
+    /// Whether the dealer's up and hole cards are a blackjack, `None` while hidden
+    fn dealer_blackjack(&self) -> Option<bool>;
+}
+
+/// A whole shoe
+impl Cards for [u8] {
+    fn card(&self, position: usize) -> Option<u8> {
+        Some(self[position])
+    }
+
+    fn dealer_blackjack(&self) -> Option<bool> {
+        Some(is_blackjack(&[self[1], self[HOLE_CARD]]))
+    }
+}
+
+/// The values of a round the house opened so far, by position
+impl Cards for BTreeMap<u32, u8> {
+    fn card(&self, position: usize) -> Option<u8> {
+        self.get(&(position as u32)).copied()
+    }
+
+    fn dealer_blackjack(&self) -> Option<bool> {
+        self.get(&(PEEK_POSITION as u32)).map(|&value| value == 1)
+    }
+}
+
+/// Cards on the table, dealt from the shoe in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub hands: Vec<Hand>,
+    /// Hand the next action plays
+    pub active: usize,
+    /// Dealer's up card, hole card and draws
+    pub dealer: Vec<u8>,
+    /// Whether insurance was taken, once the player decided
+    pub insurance: Option<bool>,
+    /// Whether the dealer has played and the hand can be settled
+    pub finished: bool,
+    /// Position the table needs opened before the next action can play out
+    pub waiting: Option<usize>,
+    /// Position of the next card in the shoe
+    next_card: usize,
+}
+
+impl Table {
+    /// Player, dealer, player, dealer; the dealer peeks right away unless showing an ace,
+    /// which waits for the insurance decision
+    fn deal<C: Cards + ?Sized>(cards: &C) -> Table {
+        let mut table = Table {
+            hands: Vec::new(),
+            active: 0,
+            dealer: Vec::new(),
+            insurance: None,
+            finished: false,
+            waiting: None,
+            next_card: 0,
+        };
+        let first = table.draw(cards);
+        let up = table.draw(cards);
+        let second = table.draw(cards);
+        // The hole card stays face down until the dealer plays
+        let hole = cards.card(HOLE_CARD).unwrap_or(HIDDEN_CARD);
+        table.next_card = HOLE_CARD + 1;
+        table.hands.push(Hand { cards: vec![first, second], doubled: false, split_aces: false, done: false });
+        table.dealer = vec![up, hole];
+
+        if table.waiting.is_none() && !table.insurance_offered() {
+            table.peek(cards);
+        }
+        table
+    }
+
+    /// Whether the player is done and the dealer plays next, once the shoe is known
+    pub fn player_done(&self) -> bool {
+        self.finished || self.waiting == Some(HOLE_CARD)
+    }
+
+    /// Whether the player still has to take or decline insurance
+    pub fn insurance_offered(&self) -> bool {
+        self.insurance.is_none() && card_value(self.dealer[0]) == 11
+    }
+
+    /// Whether the hand can be split now
+    pub fn can_split(&self) -> bool {
+        let hand = &self.hands[self.active];
+        self.hands.len() < MAX_HANDS
+            && hand.cards.len() == 2
+            && !hand.split_aces
+            && card_value(hand.cards[0]) == card_value(hand.cards[1])
+    }
+
+    /// Whether the hand can be doubled now
+    pub fn can_double(&self) -> bool {
+        let hand = &self.hands[self.active];
+        hand.cards.len() == 2 && !hand.split_aces
+    }
+
+    /// Ends the hand before any play on a blackjack on either side. Only a ten or an ace up
+    /// can make a dealer blackjack, and the house answers the peek without showing the hole card.
+    fn peek<C: Cards + ?Sized>(&mut self, cards: &C) {
+        let dealer_blackjack = card_value(self.dealer[0]) >= 10 && match cards.dealer_blackjack() {
+            Some(dealer_blackjack) => dealer_blackjack,
+            None => {
+                self.waiting.get_or_insert(PEEK_POSITION);
+                return;
+            }
+        };
+        if dealer_blackjack || is_blackjack(&self.hands[0].cards) {
+            self.finished = true;
+        }
+    }
+
+    /// The next card of the shoe, or a stand-in while the house hasn't opened it
+    fn draw<C: Cards + ?Sized>(&mut self, cards: &C) -> u8 {
+        let position = self.next_card;
+        self.next_card += 1;
+        cards.card(position).unwrap_or_else(|| {
+            self.waiting.get_or_insert(position);
+            HIDDEN_CARD
+        })
+    }
+
+    fn apply<C: Cards + ?Sized>(&mut self, shoe: &C, params: &BlackjackParams, action: BlackjackAction) -> Result<(), String> {
+        if self.finished {
+            return Err("The hand is over".to_string());
+        }
+        let insurance_decision = matches!(action, BlackjackAction::Insurance | BlackjackAction::DeclineInsurance);
+        if self.insurance_offered() != insurance_decision {
+            return Err(if insurance_decision {
+                "Insurance is only offered against an ace, before the first action".to_string()
+            } else {
+                "Take or decline insurance first".to_string()
+            });
+        }
+
+        let active = self.active;
+        match action {
+            BlackjackAction::Insurance | BlackjackAction::DeclineInsurance => {
+                self.insurance = Some(action == BlackjackAction::Insurance);
+                self.peek(shoe);
+                return Ok(());
+            }
+            BlackjackAction::Hit => {
+                let card = self.draw(shoe);
+                self.hands[active].cards.push(card);
+            }
+            BlackjackAction::Stand => self.hands[active].done = true,
+            BlackjackAction::Double => {
+                if !self.can_double() {
+                    return Err("Only a hand's first two cards can be doubled".to_string());
+                }
+                let card = self.draw(shoe);
+                let hand = &mut self.hands[active];
+                hand.cards.push(card);
+                hand.doubled = true;
+                hand.done = true;
+            }
+            BlackjackAction::Split => {
+                if !self.can_split() {
+                    return Err(format!("Only pairs can be split, into at most {} hands", MAX_HANDS));
+                }
+                let split_aces = card_value(self.hands[active].cards[0]) == 11;
+                let second = self.hands[active].cards.pop().expect("Pairs have two cards");
+                self.hands[active].split_aces = split_aces;
+                self.hands.insert(active + 1, Hand { cards: vec![second], doubled: false, split_aces, done: false });
+                // The first hand is completed now, the second once the player gets to it
+                let card = self.draw(shoe);
+                self.hands[active].cards.push(card);
+            }
+        }
+        self.advance(shoe, params);
+        Ok(())
+    }
+
+    /// Closes finished hands, moves on to the next one, and lets the dealer play after the last
+    fn advance<C: Cards + ?Sized>(&mut self, shoe: &C, params: &BlackjackParams) {
+        while self.active < self.hands.len() {
+            if self.hands[self.active].cards.len() == 1 {
+                let card = self.draw(shoe);
+                self.hands[self.active].cards.push(card);
+            }
+            let hand = &mut self.hands[self.active];
+            if hand.split_aces || hand_total(&hand.cards).0 >= 21 {
+                hand.done = true;
+            }
+            if !hand.done {
+                return;
+            }
+            self.active += 1;
+        }
+        self.play_dealer(shoe, params);
+    }
+
+    /// The dealer draws to 17, hitting soft 17 if the rules say so, unless every hand busted.
+    /// Played from opened cards, the hand waits at the hole card for the rest of the shoe.
+    fn play_dealer<C: Cards + ?Sized>(&mut self, shoe: &C, params: &BlackjackParams) {
+        if shoe.card(HOLE_CARD).is_none() {
+            self.waiting.get_or_insert(HOLE_CARD);
+            return;
+        }
+        if self.hands.iter().any(|hand| hand_total(&hand.cards).0 <= 21) {
+            loop {
+                let (total, soft) = hand_total(&self.dealer);
+                if total > 17 || (total == 17 && !(soft && params.rules.dealer_hits_soft_17)) {
+                    break;
+                }
+                let card = self.draw(shoe);
+                self.dealer.push(card);
+            }
+        }
+        self.finished = true;
+    }
+
+    /// Declines a pending insurance and stands every hand still in play
+    fn stand_all<C: Cards + ?Sized>(&mut self, shoe: &C, params: &BlackjackParams) {
+        if self.insurance_offered() {
+            self.apply(shoe, params, BlackjackAction::DeclineInsurance)
+                .expect("Insurance can be declined while offered");
+        }
+        while !self.finished {
+            self.apply(shoe, params, BlackjackAction::Stand)
+                .expect("Hands in play can stand");
+        }
+    }
+
+    /// What a hand returns, in hundredths of the initial bet
+    pub fn hand_multiplier(&self, params: &BlackjackParams, index: usize) -> u32 {
+        let hand = &self.hands[index];
+        let stake = if hand.doubled { 200 } else { 100 };
+        let (player, _) = hand_total(&hand.cards);
+        let (dealer, _) = hand_total(&self.dealer);
+        // Only the original two cards count as a blackjack, not 21 after a split
+        let player_blackjack = self.hands.len() == 1 && is_blackjack(&hand.cards);
+        let dealer_blackjack = is_blackjack(&self.dealer);
+        if player > 21 {
+            0
+        } else if player_blackjack && dealer_blackjack {
+            100
+        } else if player_blackjack {
+            100 + blackjack_bonus(params)
+        } else if dealer_blackjack || (dealer <= 21 && dealer > player) {
+            0
+        } else if dealer == player {
+            stake
+        } else {
+            stake * 2
+        }
+    }
+
+    /// Insurance pays 2:1 on half the initial bet if the dealer has a blackjack
+    pub fn insurance_multiplier(&self) -> u32 {
+        if self.insurance == Some(true) && is_blackjack(&self.dealer) {
+            150
+        } else {
+            0
+        }
+    }
+}
+
+/// A shoe of [`DECKS`] decks shuffled from the entropy, as card indices 0 to 51
+pub fn shoe(entropy: &[u8; 32]) -> Vec<u8> {
+    let mut shoe: Vec<u8> = (0..52 * DECKS).map(|index| (index % 52) as u8).collect();
+    EntropyStream::new(entropy, b"blackjack").shuffle(&mut shoe);
+    shoe
+}
+
+/// Deals from the shoe and plays the recorded actions, rejecting any the table doesn't allow
+pub fn play(shoe: &[u8], params: &BlackjackParams) -> Result<Table, String> {
+    replay(shoe, params)
+}
+
+/// Plays the recorded actions on the cards a round opened so far. The table is the one before
+/// the first action that needs a hidden position, which it names as `waiting`.
+pub fn play_opened(cards: &BTreeMap<u32, u8>, params: &BlackjackParams) -> Result<Table, String> {
+    replay(cards, params)
+}
+
+/// Plays on for a player who left the hand: declines a pending insurance and stands every hand,
+/// as far as the opened cards go. The stands are recorded in `params`, and the table stops at
+/// the next position the house has to open.
+pub fn stand_opened(cards: &BTreeMap<u32, u8>, params: &mut BlackjackParams) -> Result<Table, String> {
+    loop {
+        let table = play_opened(cards, params)?;
+        if table.waiting.is_some() || table.finished {
+            return Ok(table);
+        }
+        let action = if table.insurance_offered() { BlackjackAction::DeclineInsurance } else { BlackjackAction::Stand };
+        params.actions.push(action);
+    }
+}
+
+fn replay<C: Cards + ?Sized>(cards: &C, params: &BlackjackParams) -> Result<Table, String> {
+    let mut table = Table::deal(cards);
+    if table.waiting.is_some() {
+        return Ok(table);
+    }
+    for &action in &params.actions {
+        let mut next = table.clone();
+        next.apply(cards, params, action)?;
+        if next.waiting.is_some() {
+            table.waiting = next.waiting;
+            return Ok(table);
+        }
+        table = next;
+    }
+    Ok(table)
+}
+
+/// The values the house commits to for a shoe: its cards by position, then whether the dealer
+/// has a blackjack at [`PEEK_POSITION`]
+pub fn committed_values(shoe: &[u8]) -> Vec<u8> {
+    let mut values = shoe.to_vec();
+    values.push(shoe.dealer_blackjack().unwrap_or_default() as u8);
+    values
+}
+
+/// Whether a position can be opened with a value while the hand is played: a card of the
+/// shoe other than the hole card, or the dealer's peek
+pub fn is_valid_opening(position: u32, value: u8) -> bool {
+    match position as usize {
+        HOLE_CARD => false,
+        PEEK_POSITION => value <= 1,
+        position => position < PEEK_POSITION && value < 52,
+    }
+}
+
+/// Stake (hundredths of the initial bet) an action adds, and the worst case it adds for the house
+pub fn action_stake(action: BlackjackAction) -> (u32, u32) {
+    match action {
+        // Doubles and splits stake one more bet that pays at most even money
+        BlackjackAction::Double | BlackjackAction::Split => (100, 100),
+        // Insurance stakes half a bet at 2:1
+        BlackjackAction::Insurance => (50, 100),
+        BlackjackAction::Hit | BlackjackAction::Stand | BlackjackAction::DeclineInsurance => (0, 0),
+    }
+}
+
+/// Everything staked with the actions so far, in hundredths of the initial bet
+pub fn total_stake(params: &BlackjackParams) -> u32 {
+    100 + params.actions.iter().map(|&action| action_stake(action).0).sum::<u32>()
+}
+
+/// What a blackjack pays on top of the stake, in hundredths of the initial bet
+fn blackjack_bonus(params: &BlackjackParams) -> u32 {
+    match params.rules.blackjack_payout {
+        BlackjackPayout::ThreeToTwo => 150,
+        BlackjackPayout::SixToFive => 120,
+    }
+}
+
+/// Value of a card counting aces as 11
+fn card_value(card: u8) -> u8 {
+    match card % 13 {
+        0 => 11,
+        rank @ 1..=9 => rank + 1,
+        _ => 10,
+    }
+}
+
+/// Best total of the cards, and whether an ace still counts as 11 in it
+pub fn hand_total(cards: &[u8]) -> (u8, bool) {
+    let mut total: u8 = cards.iter().map(|&card| card_value(card)).sum();
+    let mut soft_aces = cards.iter().filter(|&&card| card_value(card) == 11).count();
+    while total > 21 && soft_aces > 0 {
+        total -= 10;
+        soft_aces -= 1;
+    }
+    (total, soft_aces > 0)
+}
+
+fn is_blackjack(cards: &[u8]) -> bool {
+    cards.len() == 2 && hand_total(cards).0 == 21
+}
+
+fn cards_label(cards: &[u8]) -> String {
+    cards.iter().map(|&card| PlayingCard::from_index(card).to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn multiplier_label(multiplier: u32) -> String {
+    format!("{}.{:02}x", multiplier / 100, multiplier % 100)
+}
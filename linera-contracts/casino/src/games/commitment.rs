@@ -0,0 +1,120 @@
+//! Merkle Commitments to Hidden Rounds
+//!
+//! Interactive games deal a board the player must not see while playing. The house commits
+//! to it with the root of a Merkle tree holding one leaf per position,
+//! SHA3(position || value || salt), then opens positions one at a time with their proof.
+//! Salts are derived from the round's entropy, so once the server seed is revealed anyone can
+//! rebuild the tree and check the house committed to the board the seed deals.
+
+use sha3::{Digest, Sha3_256};
+
+use super::randomness::hmac_sha3;
+use crate::Opening;
+
+/// Salt of a position's leaf, keeping its value hidden until it is opened
+pub fn salt(entropy: &[u8; 32], position: u32) -> [u8; 32] {
+    hmac_sha3(entropy, format!("commitment:{}", position).as_bytes())
+}
+
+/// Leaf committing to the value at a position
+pub fn leaf(position: u32, value: u8, salt: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(position.to_be_bytes());
+    hasher.update([value]);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Every level of the tree over `values`, from the leaves up to the root. The leaves are
+/// padded with zero hashes to a power of two.
+fn levels(values: &[u8], entropy: &[u8; 32]) -> Vec<Vec<[u8; 32]>> {
+    let mut leaves: Vec<[u8; 32]> = (0..values.len() as u32)
+        .map(|position| leaf(position, values[position as usize], &salt(entropy, position)))
+        .collect();
+    leaves.resize(values.len().next_power_of_two(), [0; 32]);
+
+    let mut levels = vec![leaves];
+    while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+        let parents = level.chunks(2).map(|pair| node(&pair[0], &pair[1])).collect();
+        levels.push(parents);
+    }
+    levels
+}
+
+/// Root of the commitment to the values of a round dealt from `entropy`
+pub fn root(values: &[u8], entropy: &[u8; 32]) -> [u8; 32] {
+    levels(values, entropy).last().expect("A tree has at least its leaves")[0]
+}
+
+/// Opening of a position, as the house answers the round with it
+pub fn open(values: &[u8], entropy: &[u8; 32], position: u32) -> Opening {
+    let levels = levels(values, entropy);
+    let mut index = position as usize;
+    let proof = levels[..levels.len() - 1]
+        .iter()
+        .map(|level| {
+            let sibling = level[index ^ 1];
+            index /= 2;
+            sibling
+        })
+        .collect();
+    Opening {
+        position,
+        value: values[position as usize],
+        salt: salt(entropy, position),
+        proof,
+    }
+}
+
+/// Whether the opening proves its value at its position under `root`
+pub fn verify(root: &[u8; 32], opening: &Opening) -> bool {
+    let mut hash = leaf(opening.position, opening.value, &opening.salt);
+    let mut index = opening.position as usize;
+    for sibling in &opening.proof {
+        hash = if index % 2 == 0 { node(&hash, sibling) } else { node(sibling, &hash) };
+        index /= 2;
+    }
+    index == 0 && hash == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_verify() {
+        let entropy = [9u8; 32];
+        let values: Vec<u8> = (0..25).map(|position| (position % 3 == 0) as u8).collect();
+        let root = root(&values, &entropy);
+
+        for position in 0..values.len() as u32 {
+            let opening = open(&values, &entropy, position);
+            assert_eq!(opening.value, values[position as usize]);
+            assert!(verify(&root, &opening));
+        }
+    }
+
+    #[test]
+    fn test_tampered_opening() {
+        let entropy = [9u8; 32];
+        let values = vec![0, 1, 0, 0, 1];
+        let root = root(&values, &entropy);
+
+        let mut flipped = open(&values, &entropy, 1);
+        flipped.value = 0;
+        assert!(!verify(&root, &flipped));
+
+        let mut moved = open(&values, &entropy, 2);
+        moved.position = 3;
+        assert!(!verify(&root, &moved));
+
+        assert!(!verify(&[0; 32], &open(&values, &entropy, 0)));
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Crash game logic
+
+use super::{entropy::EntropyStream, limbo, Game};
+use crate::{CrashParams, CrashResult, GameParams, GameResult, GameType, BASIS_POINTS};
+
+/// Highest crash point and auto cash-out target, 1,000x (multiplier * 100). Bets without an
+/// auto cash-out are backed up to it.
+pub const MAX_CRASH_MULTIPLIER: u32 = 100_000;
+
+/// Lowest auto cash-out target, 1.01x (multiplier * 100)
+pub const MIN_AUTO_CASH_OUT: u32 = 101;
+
+/// House edge when the operator hasn't configured one, in basis points
+pub const DEFAULT_HOUSE_EDGE_BPS: u32 = 100;
+
+/// Bets a round takes, so ending it settles them all within one block
+pub const MAX_ROUND_BETS: usize = 200;
+
+/// The curve grows by 1% every step of 100ms, doubling about every 7 seconds
+const CURVE_STEP_MICROS: u64 = 100_000;
+const CURVE_GROWTH_PERCENT: u64 = 1;
+
+/// Fixed point the curve is compounded in, 1.00x
+const CURVE_SCALE: u64 = 1_000_000;
+
+/// Crash is played in shared rounds: every bet of a round rides the same curve, which crashes
+/// at the point drawn from the round's pre-committed seed. Each player cashes out by hand at
+/// the curve's multiplier, or automatically at their target; the recorded params hold both, so
+/// a settled bet replays like any other game.
+pub struct Crash;
+
+impl Game for Crash {
+    const GAME_TYPE: GameType = GameType::Crash;
+    const INTERACTIVE: bool = true;
+    const DEFAULT_HOUSE_EDGE_BPS: Option<u32> = Some(DEFAULT_HOUSE_EDGE_BPS);
+    type Params = CrashParams;
+    /// The crash point (multiplier * 100)
+    type Outcome = u32;
+
+    fn params(game_params: &GameParams) -> Option<&CrashParams> {
+        match game_params {
+            GameParams::Crash(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the auto cash-out target is in range and nobody cashed out yet
+    fn validate(params: &CrashParams) -> Result<(), String> {
+        if let Some(target) = params.auto_cash_out {
+            if !(MIN_AUTO_CASH_OUT..=MAX_CRASH_MULTIPLIER).contains(&target) {
+                return Err(format!(
+                    "Auto cash-outs go from 1.01x to 1000.00x, not {}",
+                    multiplier_label(target)
+                ));
+            }
+        }
+        if params.house_edge_bps >= BASIS_POINTS {
+            return Err(format!("House edge of {} basis points leaves nothing to pay", params.house_edge_bps));
+        }
+        if params.cashed_out_at.is_some() {
+            return Err("Bets are cashed out once the round is running".to_string());
+        }
+        Ok(())
+    }
+
+    /// The auto cash-out target, or the highest crash point without one
+    fn max_multiplier(params: &CrashParams) -> u32 {
+        params.auto_cash_out.unwrap_or(MAX_CRASH_MULTIPLIER)
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &CrashParams) -> Option<u32> {
+        match algorithm_version {
+            4 => Some(crash_point(entropy, params.house_edge_bps)),
+            _ => None,
+        }
+    }
+
+    /// The multiplier the bet left the curve at, if the curve hadn't crashed yet
+    fn multiplier(params: &CrashParams, crash_point: &u32) -> u32 {
+        match cashed_out_at(params) {
+            Some(multiplier) if multiplier <= *crash_point => multiplier,
+            _ => 0,
+        }
+    }
+
+    fn serialize_outcome(params: &CrashParams, crash_point: &u32) -> String {
+        match cashed_out_at(params) {
+            Some(multiplier) => format!(
+                "Crash: {}, Cashed out {}",
+                multiplier_label(*crash_point),
+                multiplier_label(multiplier)
+            ),
+            None => format!("Crash: {}, No cash-out", multiplier_label(*crash_point)),
+        }
+    }
+
+    fn result(params: &CrashParams, crash_point: &u32) -> GameResult {
+        GameResult {
+            crash: Some(CrashResult {
+                crash_point: *crash_point,
+                cashed_out_at: cashed_out_at(params),
+                won: Self::multiplier(params, crash_point) > 0,
+            }),
+            ..GameResult::default()
+        }
+    }
+
+    fn set_house_edge(game_params: &mut GameParams, house_edge_bps: u32) {
+        if let GameParams::Crash(params) = game_params {
+            params.house_edge_bps = house_edge_bps;
+        }
+    }
+
+    /// Crash was added after params were typed
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        None
+    }
+}
+
+/// Crash point (* 100) of a round, drawn like a limbo result and capped at
+/// [`MAX_CRASH_MULTIPLIER`]
+pub fn crash_point(entropy: &[u8; 32], house_edge_bps: u32) -> u32 {
+    let random = EntropyStream::new(entropy, b"crash").next_u32();
+    limbo::draw(random, house_edge_bps).min(MAX_CRASH_MULTIPLIER)
+}
+
+/// Multiplier (* 100) of the curve `elapsed_micros` after the round started: 1.00x, compounded
+/// by 1% every 100ms up to [`MAX_CRASH_MULTIPLIER`]
+pub fn curve_multiplier(elapsed_micros: u64) -> u32 {
+    let cap = MAX_CRASH_MULTIPLIER as u64 * (CURVE_SCALE / 100);
+    let mut scaled = CURVE_SCALE;
+    for _ in 0..elapsed_micros / CURVE_STEP_MICROS {
+        scaled += scaled * CURVE_GROWTH_PERCENT / 100;
+        if scaled >= cap {
+            return MAX_CRASH_MULTIPLIER;
+        }
+    }
+    (scaled / (CURVE_SCALE / 100)) as u32
+}
+
+/// Whether the curve has passed the crash point `elapsed_micros` after the round started. A
+/// cash-out at exactly the crash point still pays, so the round only crashes above it, or once
+/// the curve stops at [`MAX_CRASH_MULTIPLIER`].
+pub fn has_crashed(elapsed_micros: u64, crash_point: u32) -> bool {
+    let multiplier = curve_multiplier(elapsed_micros);
+    multiplier > crash_point || multiplier == MAX_CRASH_MULTIPLIER
+}
+
+/// How long the curve takes to reach [`MAX_CRASH_MULTIPLIER`], by when every round has crashed
+pub fn max_curve_micros() -> u64 {
+    let cap = MAX_CRASH_MULTIPLIER as u64 * (CURVE_SCALE / 100);
+    let mut scaled = CURVE_SCALE;
+    let mut steps = 0;
+    while scaled < cap {
+        scaled += scaled * CURVE_GROWTH_PERCENT / 100;
+        steps += 1;
+    }
+    steps * CURVE_STEP_MICROS
+}
+
+/// Multiplier (* 100) a bet is paid when its round expires without the seed: where it left the
+/// curve, or the top of the curve for bets still riding it
+pub fn expired_multiplier(params: &CrashParams) -> u32 {
+    cashed_out_at(params).unwrap_or(MAX_CRASH_MULTIPLIER)
+}
+
+/// Outcome details of a bet whose round expired without the seed: refunded if the round never
+/// started, otherwise paid at [`expired_multiplier`]
+pub fn expired_details(params: &CrashParams, started: bool) -> String {
+    if started {
+        format!("Expired, Paid {}", multiplier_label(expired_multiplier(params)))
+    } else {
+        "Expired, Refunded".to_string()
+    }
+}
+
+/// Where the bet left the curve: the earlier of its manual cash-out and its auto target
+fn cashed_out_at(params: &CrashParams) -> Option<u32> {
+    match (params.cashed_out_at, params.auto_cash_out) {
+        (Some(manual), Some(auto)) => Some(manual.min(auto)),
+        (manual, auto) => manual.or(auto),
+    }
+}
+
+fn multiplier_label(multiplier: u32) -> String {
+    format!("{}.{:02}x", multiplier / 100, multiplier % 100)
+}
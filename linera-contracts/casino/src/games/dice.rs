@@ -0,0 +1,129 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Dice game logic
+
+use super::{entropy::EntropyStream, Game};
+use crate::{DiceDirection, DiceParams, DiceResult, GameParams, GameResult, GameType, BASIS_POINTS};
+
+/// Rolls are drawn from 0.00 to 99.99, in hundredths
+pub const ROLL_OUTCOMES: u32 = 10_000;
+
+/// Lowest and highest target, 0.01 and 98.00
+pub const MIN_TARGET: u32 = 1;
+pub const MAX_TARGET: u32 = 9_800;
+
+/// Highest chance of winning a bet may have, 98% of the rolls, so every win pays more than the
+/// stake back
+const MAX_WINNING_ROLLS: u32 = 9_800;
+
+/// House edge when the operator hasn't configured one, in basis points
+pub const DEFAULT_HOUSE_EDGE_BPS: u32 = 100;
+
+/// The player picks a target and whether the roll lands over or under it; the fewer rolls
+/// win, the more a win pays.
+pub struct Dice;
+
+impl Game for Dice {
+    const GAME_TYPE: GameType = GameType::Dice;
+    const DEFAULT_HOUSE_EDGE_BPS: Option<u32> = Some(DEFAULT_HOUSE_EDGE_BPS);
+    type Params = DiceParams;
+    /// The roll, in hundredths
+    type Outcome = u32;
+
+    fn params(game_params: &GameParams) -> Option<&DiceParams> {
+        match game_params {
+            GameParams::Dice(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the target is on the dice and leaves a win worth more than the stake
+    fn validate(params: &DiceParams) -> Result<(), String> {
+        if !(MIN_TARGET..=MAX_TARGET).contains(&params.target) {
+            return Err(format!("Targets go from 0.01 to 98.00, not {}", hundredths(params.target)));
+        }
+        if winning_rolls(params) > MAX_WINNING_ROLLS {
+            return Err(format!(
+                "Rolling {:?} {} wins {}% of the time, more than 98%",
+                params.direction,
+                hundredths(params.target),
+                hundredths(winning_rolls(params))
+            ));
+        }
+        if params.house_edge_bps >= BASIS_POINTS {
+            return Err(format!("House edge of {} basis points leaves nothing to pay", params.house_edge_bps));
+        }
+        Ok(())
+    }
+
+    /// A win pays the same whatever the roll
+    fn max_multiplier(params: &DiceParams) -> u32 {
+        win_multiplier(params)
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], _params: &DiceParams) -> Option<u32> {
+        match algorithm_version {
+            4 => Some(EntropyStream::new(entropy, b"dice").uniform(ROLL_OUTCOMES)),
+            _ => None,
+        }
+    }
+
+    fn multiplier(params: &DiceParams, roll: &u32) -> u32 {
+        if wins(params, *roll) {
+            win_multiplier(params)
+        } else {
+            0
+        }
+    }
+
+    fn serialize_outcome(params: &DiceParams, roll: &u32) -> String {
+        format!("Dice: Roll {}, {:?} {}", hundredths(*roll), params.direction, hundredths(params.target))
+    }
+
+    fn result(params: &DiceParams, roll: &u32) -> GameResult {
+        GameResult {
+            dice: Some(DiceResult { roll: *roll, won: wins(params, *roll) }),
+            ..GameResult::default()
+        }
+    }
+
+    fn set_house_edge(game_params: &mut GameParams, house_edge_bps: u32) {
+        if let GameParams::Dice(params) = game_params {
+            params.house_edge_bps = house_edge_bps;
+        }
+    }
+
+    /// Dice was added after params were typed
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        None
+    }
+}
+
+/// Whether the roll wins: strictly over the target, or strictly under it
+fn wins(params: &DiceParams, roll: u32) -> bool {
+    match params.direction {
+        DiceDirection::Over => roll > params.target,
+        DiceDirection::Under => roll < params.target,
+    }
+}
+
+/// Rolls out of [`ROLL_OUTCOMES`] that win
+pub fn winning_rolls(params: &DiceParams) -> u32 {
+    match params.direction {
+        DiceDirection::Over => (ROLL_OUTCOMES - 1).saturating_sub(params.target),
+        DiceDirection::Under => params.target.min(ROLL_OUTCOMES),
+    }
+}
+
+/// Multiplier (* 100) of a win: the inverse of the win chance less the house edge, rounded down
+pub fn win_multiplier(params: &DiceParams) -> u32 {
+    let winning = winning_rolls(params) as u64;
+    if winning == 0 {
+        return 0;
+    }
+    let returned = ROLL_OUTCOMES as u64 * BASIS_POINTS.saturating_sub(params.house_edge_bps) as u64;
+    (returned / (winning * 100)) as u32
+}
+
+fn hundredths(value: u32) -> String {
+    format!("{}.{:02}", value / 100, value % 100)
+}
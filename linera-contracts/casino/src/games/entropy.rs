@@ -0,0 +1,170 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Shared entropy derivation for all games
+
+use sha3::{Digest, Sha3_256};
+
+/// Version of the sampling algorithm, stored on every outcome.
+///
+/// - 1: each game hashed `entropy || tag` once and reduced `u32`s with `%`
+/// - 2: domain-separated counter stream with rejection sampling ([`EntropyStream`])
+/// - 3: Plinko lands in the slot of its right bounces and pays per-row risk tables
+/// - 4: Wheel spins the layout of its segment count and risk level
+pub const ALGORITHM_VERSION: u8 = 4;
+
+/// Domain separator for game entropy streams
+const STREAM_DOMAIN: &[u8] = b"apt-casino/stream/v2";
+
+/// A deterministic stream of random words derived from a game's entropy.
+///
+/// Block `i` is `SHA3(domain || len(tag) || tag || entropy || i)`, so each game draws from
+/// its own stream and never runs out of fresh bytes.
+pub struct EntropyStream {
+    entropy: [u8; 32],
+    tag: &'static [u8],
+    counter: u64,
+    block: [u8; 32],
+    offset: usize,
+}
+
+impl EntropyStream {
+    pub fn new(entropy: &[u8; 32], tag: &'static [u8]) -> Self {
+        EntropyStream {
+            entropy: *entropy,
+            tag,
+            counter: 0,
+            block: [0u8; 32],
+            // Forces the first block to be generated on the first draw
+            offset: 32,
+        }
+    }
+
+    fn refill(&mut self) {
+        let mut hasher = Sha3_256::new();
+        hasher.update(STREAM_DOMAIN);
+        hasher.update([self.tag.len() as u8]);
+        hasher.update(self.tag);
+        hasher.update(self.entropy);
+        hasher.update(self.counter.to_be_bytes());
+        self.block = hasher.finalize().into();
+        self.counter += 1;
+        self.offset = 0;
+    }
+
+    /// Next 32 random bits
+    pub fn next_u32(&mut self) -> u32 {
+        if self.offset + 4 > self.block.len() {
+            self.refill();
+        }
+        let bytes = [
+            self.block[self.offset],
+            self.block[self.offset + 1],
+            self.block[self.offset + 2],
+            self.block[self.offset + 3],
+        ];
+        self.offset += 4;
+        u32::from_be_bytes(bytes)
+    }
+
+    /// Uniform value in `[0, n)` without modulo bias
+    pub fn uniform(&mut self, n: u32) -> u32 {
+        assert!(n > 0, "Cannot sample from an empty range");
+        // Reject the lowest `2^32 mod n` values so the accepted range is a multiple of n
+        let threshold = ((1u64 << 32) % n as u64) as u32;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return value % n;
+            }
+        }
+    }
+
+    /// `count` fair coin flips, e.g. left/right bounces of a ball
+    pub fn bits(&mut self, count: usize) -> Vec<bool> {
+        let mut bits = Vec::with_capacity(count);
+        while bits.len() < count {
+            let word = self.next_u32();
+            let take = (count - bits.len()).min(32);
+            bits.extend((0..take).map(|i| (word >> (31 - i)) & 1 == 1));
+        }
+        bits
+    }
+
+    /// Uniform Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.uniform(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// `count` distinct values out of `0..total`, in draw order
+    pub fn sample_distinct(&mut self, total: u32, count: u32) -> Vec<u32> {
+        let mut remaining: Vec<u32> = (0..total).collect();
+        let count = count.min(total);
+        (0..count)
+            .map(|_| {
+                let idx = self.uniform(remaining.len() as u32) as usize;
+                remaining.swap_remove(idx)
+            })
+            .collect()
+    }
+}
+
+/// Algorithm v1 hash: `SHA3(entropy || tag)`, kept to verify old outcomes
+pub fn legacy_hash(entropy: &[u8; 32], tag: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(entropy);
+    hasher.update(tag);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_deterministic_and_domain_separated() {
+        let entropy = [5u8; 32];
+        let mut a = EntropyStream::new(&entropy, b"roulette");
+        let mut b = EntropyStream::new(&entropy, b"roulette");
+        let mut c = EntropyStream::new(&entropy, b"wheel");
+
+        let first: Vec<u32> = (0..20).map(|_| a.next_u32()).collect();
+        let second: Vec<u32> = (0..20).map(|_| b.next_u32()).collect();
+        let other: Vec<u32> = (0..20).map(|_| c.next_u32()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_uniform_covers_range_evenly() {
+        let mut stream = EntropyStream::new(&[9u8; 32], b"test");
+        let mut counts = [0u32; 37];
+        for _ in 0..37_000 {
+            counts[stream.uniform(37) as usize] += 1;
+        }
+        // Every pocket shows up, and none is wildly over-represented
+        assert!(counts.iter().all(|&c| (800..1200).contains(&c)), "{:?}", counts);
+    }
+
+    #[test]
+    fn test_sample_distinct() {
+        let mut stream = EntropyStream::new(&[1u8; 32], b"mines");
+        let mut picks = stream.sample_distinct(64, 40);
+        assert_eq!(picks.len(), 40);
+        picks.sort();
+        picks.dedup();
+        assert_eq!(picks.len(), 40, "Picks must be distinct");
+        assert!(picks.iter().all(|&p| p < 64));
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut stream = EntropyStream::new(&[2u8; 32], b"cards");
+        let mut deck: Vec<u32> = (0..52).collect();
+        stream.shuffle(&mut deck);
+        assert_ne!(deck, (0..52).collect::<Vec<_>>());
+        deck.sort();
+        assert_eq!(deck, (0..52).collect::<Vec<_>>());
+    }
+}
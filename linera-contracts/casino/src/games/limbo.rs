@@ -0,0 +1,100 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Limbo game logic
+
+use super::{entropy::EntropyStream, Game, MAX_MULTIPLIER};
+use crate::{GameParams, GameResult, GameType, LimboParams, LimboResult, BASIS_POINTS};
+
+/// Lowest target, 1.01x (multiplier * 100); the highest is [`MAX_MULTIPLIER`]
+pub const MIN_TARGET: u32 = 101;
+
+/// House edge when the operator hasn't configured one, in basis points
+pub const DEFAULT_HOUSE_EDGE_BPS: u32 = 100;
+
+/// The entropy draws a multiplier, and the bet pays its target if the draw reaches it.
+pub struct Limbo;
+
+impl Game for Limbo {
+    const GAME_TYPE: GameType = GameType::Limbo;
+    const DEFAULT_HOUSE_EDGE_BPS: Option<u32> = Some(DEFAULT_HOUSE_EDGE_BPS);
+    type Params = LimboParams;
+    /// The drawn multiplier (* 100)
+    type Outcome = u32;
+
+    fn params(game_params: &GameParams) -> Option<&LimboParams> {
+        match game_params {
+            GameParams::Limbo(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the target is in range
+    fn validate(params: &LimboParams) -> Result<(), String> {
+        if !(MIN_TARGET..=MAX_MULTIPLIER).contains(&params.target) {
+            return Err(format!(
+                "Targets go from 1.01x to 1000000.00x, not {}",
+                multiplier_label(params.target)
+            ));
+        }
+        if params.house_edge_bps >= BASIS_POINTS {
+            return Err(format!("House edge of {} basis points leaves nothing to pay", params.house_edge_bps));
+        }
+        Ok(())
+    }
+
+    /// A win pays the target
+    fn max_multiplier(params: &LimboParams) -> u32 {
+        params.target
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &LimboParams) -> Option<u32> {
+        match algorithm_version {
+            4 => Some(draw(EntropyStream::new(entropy, b"limbo").next_u32(), params.house_edge_bps)),
+            _ => None,
+        }
+    }
+
+    fn multiplier(params: &LimboParams, drawn: &u32) -> u32 {
+        if *drawn >= params.target {
+            params.target
+        } else {
+            0
+        }
+    }
+
+    fn serialize_outcome(params: &LimboParams, drawn: &u32) -> String {
+        format!("Limbo: {}, Target {}", multiplier_label(*drawn), multiplier_label(params.target))
+    }
+
+    fn result(params: &LimboParams, drawn: &u32) -> GameResult {
+        GameResult {
+            limbo: Some(LimboResult { multiplier: *drawn, won: *drawn >= params.target }),
+            ..GameResult::default()
+        }
+    }
+
+    fn set_house_edge(game_params: &mut GameParams, house_edge_bps: u32) {
+        if let GameParams::Limbo(params) = game_params {
+            params.house_edge_bps = house_edge_bps;
+        }
+    }
+
+    /// Limbo was added after params were typed
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        None
+    }
+}
+
+/// Multiplier (* 100) drawn from 32 random bits: `(1 - edge) / u` for `u` uniform in `(0, 1]`,
+/// rounded down, so any target `t` is reached with probability `(1 - edge) / t`. Draws under
+/// 1.00x show as 1.00x and draws over [`MAX_MULTIPLIER`] as the cap, neither of which changes
+/// whether a target in range is reached.
+pub fn draw(random: u32, house_edge_bps: u32) -> u32 {
+    let range = 1u128 << 32;
+    let returned = range * BASIS_POINTS.saturating_sub(house_edge_bps) as u128;
+    let drawn = returned / ((range - random as u128) * (BASIS_POINTS / 100) as u128);
+    drawn.clamp(100, MAX_MULTIPLIER as u128) as u32
+}
+
+fn multiplier_label(multiplier: u32) -> String {
+    format!("{}.{:02}x", multiplier / 100, multiplier % 100)
+}
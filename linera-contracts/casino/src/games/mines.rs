@@ -1,28 +1,161 @@
 // Copyright (c) APT Casino. All rights reserved.
 // Mines game logic
 
-use sha3::{Digest, Sha3_256};
-
-/// Calculate mines outcome from reveal value
-/// Returns (outcome_string, multiplier * 100)
-pub fn calculate_outcome(reveal_value: &[u8; 32], game_params: &str) -> (String, u32) {
-    // Parse game params: "num_mines:cells_revealed"
-    let parts: Vec<&str> = game_params.split(':').collect();
-    let num_mines: u32 = parts.get(0).and_then(|s| s.parse().ok()).unwrap_or(5);
-    let cells_revealed: u32 = parts.get(1).and_then(|s| s.parse().ok()).unwrap_or(0);
-    
-    let total_cells: u32 = 25; // 5x5 grid
-    let num_mines = num_mines.clamp(1, 24);
-    
-    // Generate mine positions using Fisher-Yates shuffle
-    let mut hasher = Sha3_256::new();
-    hasher.update(reveal_value);
-    hasher.update(b"mines");
-    let hash: [u8; 32] = hasher.finalize().into();
-    
+use super::{
+    entropy::{legacy_hash, EntropyStream},
+    Game, MAX_MULTIPLIER,
+};
+use crate::{GameParams, GameType, MinesParams, BASIS_POINTS};
+
+/// Smallest and largest board side, for grids of 3x3 to 8x8 cells
+pub const MIN_GRID_SIZE: u8 = 3;
+pub const MAX_GRID_SIZE: u8 = 8;
+
+/// House edge when the operator hasn't configured one, in basis points
+pub const DEFAULT_HOUSE_EDGE_BPS: u32 = 300;
+
+/// Mines is played tile by tile: a session bet deals a board the house commits to, then the
+/// player reveals cells until they hit a mine, clear the board or cash out. The recorded params
+/// list the picks in order, so a finished round replays like any other game.
+pub struct Mines;
+
+impl Game for Mines {
+    const GAME_TYPE: GameType = GameType::Mines;
+    const INTERACTIVE: bool = true;
+    const DEFAULT_HOUSE_EDGE_BPS: Option<u32> = Some(DEFAULT_HOUSE_EDGE_BPS);
+    type Params = MinesParams;
+    /// Positions of the mines on the board
+    type Outcome = Vec<u32>;
+
+    fn params(game_params: &GameParams) -> Option<&MinesParams> {
+        match game_params {
+            GameParams::Mines(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the board exists, has room for the mines and no cell is revealed yet
+    fn validate(params: &MinesParams) -> Result<(), String> {
+        if !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&params.grid_size) {
+            return Err(format!(
+                "Boards are {}x{} to {}x{}, not {}x{}",
+                MIN_GRID_SIZE, MIN_GRID_SIZE, MAX_GRID_SIZE, MAX_GRID_SIZE, params.grid_size, params.grid_size
+            ));
+        }
+        let mines = params.mines as u32;
+        if !(1..total_cells(params)).contains(&mines) {
+            return Err(format!("A {}x{} board holds 1 to {} mines, not {}",
+                params.grid_size, params.grid_size, total_cells(params) - 1, mines));
+        }
+        if params.house_edge_bps >= BASIS_POINTS {
+            return Err(format!("House edge of {} basis points leaves nothing to pay", params.house_edge_bps));
+        }
+        if !params.picks.is_empty() {
+            return Err("Cells are revealed one at a time once the bet is placed".to_string());
+        }
+        Ok(())
+    }
+
+    /// Multiplier of a cleared board, revealing every safe cell
+    fn max_multiplier(params: &MinesParams) -> u32 {
+        cash_out_multiplier(params, safe_cells(params))
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &MinesParams) -> Option<Vec<u32>> {
+        let num_mines = params.mines as u32;
+        match algorithm_version {
+            // Draw distinct mine positions from a fresh stream, however many mines there are
+            2..=4 => Some(EntropyStream::new(entropy, b"mines").sample_distinct(total_cells(params), num_mines)),
+            1 => Some(mine_positions_v1(entropy, total_cells(params), num_mines)),
+            _ => None,
+        }
+    }
+
+    /// Nothing if a pick hit a mine, otherwise the multiplier of the safe cells revealed. A
+    /// round cashed out on timeout before its first reveal returns the stake.
+    fn multiplier(params: &MinesParams, mine_positions: &Vec<u32>) -> u32 {
+        if params.picks.is_empty() {
+            return 100;
+        }
+        if params.picks.iter().any(|&cell| mine_positions.contains(&(cell as u32))) {
+            return 0;
+        }
+        cash_out_multiplier(params, params.picks.len() as u32)
+    }
+
+    fn serialize_outcome(params: &MinesParams, mine_positions: &Vec<u32>) -> String {
+        let list = |cells: Vec<String>| cells.join(",");
+        format!(
+            "Mines: {} mines at [{}], revealed [{}]",
+            params.mines,
+            list(mine_positions.iter().map(|p| p.to_string()).collect()),
+            list(params.picks.iter().map(|p| p.to_string()).collect()),
+        )
+    }
+
+    fn set_house_edge(game_params: &mut GameParams, house_edge_bps: u32) {
+        if let GameParams::Mines(params) = game_params {
+            params.house_edge_bps = house_edge_bps;
+        }
+    }
+
+    /// Bets recorded as "num_mines:cells_revealed" were paid for a number of cells without
+    /// looking at the board, which tile-by-tile rounds can't replay
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        None
+    }
+}
+
+/// Cells on the board
+pub fn total_cells(params: &MinesParams) -> u32 {
+    params.grid_size as u32 * params.grid_size as u32
+}
+
+/// Cells without a mine
+pub fn safe_cells(params: &MinesParams) -> u32 {
+    total_cells(params) - params.mines as u32
+}
+
+/// The board as the house commits to it, 1 for each mine and 0 for each safe cell, row by row
+pub fn board(params: &MinesParams, mine_positions: &[u32]) -> Vec<u8> {
+    (0..total_cells(params)).map(|cell| mine_positions.contains(&cell) as u8).collect()
+}
+
+/// Multiplier (* 100) of cashing out after `revealed` safe cells, nothing before the first one.
+///
+/// Surviving `k` picks among `n` cells with `m` mines has probability `C(n - m, k) / C(n, k)`,
+/// so the multiplier is `C(n, k) / C(n - m, k)` less the house edge, computed exactly in
+/// integers and rounded down. It never pays less than the stake back, nor more than
+/// [`MAX_MULTIPLIER`].
+pub fn cash_out_multiplier(params: &MinesParams, revealed: u32) -> u32 {
+    if revealed == 0 {
+        return 0;
+    }
+    let revealed = revealed.min(safe_cells(params));
+    let numerator = binomial(total_cells(params), revealed) * (BASIS_POINTS - params.house_edge_bps) as u128;
+    let denominator = binomial(safe_cells(params), revealed) * (BASIS_POINTS / 100) as u128;
+    let multiplier = (numerator / denominator).min(MAX_MULTIPLIER as u128) as u32;
+    multiplier.max(100) // Minimum 1x return
+}
+
+/// Cash-out multipliers (* 100) after each safe cell, from the first to a cleared board
+pub fn multiplier_table(params: &MinesParams) -> Vec<u32> {
+    (1..=safe_cells(params)).map(|revealed| cash_out_multiplier(params, revealed)).collect()
+}
+
+/// `C(n, k)`; exact for any board, as `C(64, 32)` times a step stays far below `u128::MAX`
+fn binomial(n: u32, k: u32) -> u128 {
+    (0..k as u128).fold(1, |binomial, i| binomial * (n as u128 - i) / (i + 1))
+}
+
+/// Algorithm v1 mine positions (hash bytes reused cyclically, modulo-biased), kept to verify
+/// old results
+fn mine_positions_v1(entropy: &[u8; 32], total_cells: u32, num_mines: u32) -> Vec<u32> {
+    let hash = legacy_hash(entropy, b"mines");
+
     let mut positions: Vec<u32> = (0..total_cells).collect();
     let mut mine_positions = Vec::new();
-    
+
     for i in 0..num_mines {
         let byte_idx = ((i * 4) % 32) as usize;
         let random_u32 = u32::from_be_bytes([
@@ -31,7 +164,7 @@ pub fn calculate_outcome(reveal_value: &[u8; 32], game_params: &str) -> (String,
             hash[(byte_idx + 2) % 32],
             hash[(byte_idx + 3) % 32],
         ]);
-        
+
         let remaining = positions.len() as u32;
         if remaining == 0 {
             break;
@@ -39,49 +172,5 @@ pub fn calculate_outcome(reveal_value: &[u8; 32], game_params: &str) -> (String,
         let idx = (random_u32 % remaining) as usize;
         mine_positions.push(positions.remove(idx));
     }
-    
-    // Calculate multiplier based on how many safe cells were revealed
-    // Multiplier increases exponentially with each reveal
-    let safe_cells = total_cells - num_mines;
-    let multiplier = if cells_revealed == 0 {
-        0 // Cashed out immediately or hit a mine on first reveal
-    } else if cells_revealed >= safe_cells {
-        // Revealed all safe cells - jackpot!
-        calculate_mines_multiplier(num_mines, safe_cells)
-    } else {
-        calculate_mines_multiplier(num_mines, cells_revealed)
-    };
-    
-    let mine_str: String = mine_positions.iter()
-        .map(|p| p.to_string())
-        .collect::<Vec<_>>()
-        .join(",");
-    
-    (format!("Mines: {} mines at [{}], {} revealed", num_mines, mine_str, cells_revealed), multiplier)
-}
-
-/// Calculate multiplier for mines game
-/// Formula: probability of surviving = (safe_cells - revealed) / (total - revealed)
-/// Multiplier = 1 / cumulative_probability
-fn calculate_mines_multiplier(num_mines: u32, cells_revealed: u32) -> u32 {
-    let total = 25u64;
-    let safe = (25 - num_mines) as u64;
-    
-    // Calculate cumulative probability of surviving each reveal
-    // P = (safe/total) * ((safe-1)/(total-1)) * ... * ((safe-n+1)/(total-n+1))
-    let mut numerator: u64 = 1;
-    let mut denominator: u64 = 1;
-    
-    for i in 0..cells_revealed as u64 {
-        numerator *= safe - i;
-        denominator *= total - i;
-    }
-    
-    if numerator == 0 {
-        return 0;
-    }
-    
-    // Multiplier = (1 / probability) * 100 with 3% house edge
-    let multiplier = ((denominator * 97) / numerator) as u32;
-    multiplier.max(100) // Minimum 1x return
+    mine_positions
 }
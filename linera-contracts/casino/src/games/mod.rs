@@ -5,3 +5,324 @@ pub mod roulette;
 pub mod plinko;
 pub mod mines;
 pub mod wheel;
+pub mod dice;
+pub mod limbo;
+pub mod crash;
+pub mod blackjack;
+pub mod baccarat;
+pub mod randomness;
+pub mod commitment;
+pub mod entropy;
+
+use std::marker::PhantomData;
+
+use linera_sdk::linera_base_types::Amount;
+
+use crate::{CasinoError, GameParams, GameResult, GameType};
+
+/// Highest multiplier (* 100) any game pays, 1,000,000x
+pub const MAX_MULTIPLIER: u32 = 100_000_000;
+
+/// A casino game settled from the 32 bytes of entropy of a bet.
+///
+/// Implementing it and listing the game in [`registry`] is all the contract and service need
+/// to accept bets on a game, besides its [`GameType`] and [`GameParams`] variants.
+pub trait Game {
+    const GAME_TYPE: GameType;
+    /// Whether the player keeps acting after the bet. The outcome must stay hidden meanwhile,
+    /// so such games are only dealt from session seeds and settled by their own operations.
+    const INTERACTIVE: bool = false;
+    /// House edge in basis points for games whose payouts follow from their odds, used until
+    /// the operator configures one. `None` for games paying fixed tables.
+    const DEFAULT_HOUSE_EDGE_BPS: Option<u32> = None;
+    /// What the player chooses when betting
+    type Params;
+    /// What the entropy decides, before it is paid against the params
+    type Outcome;
+
+    /// This game's params, if `game_params` are for it
+    fn params(game_params: &GameParams) -> Option<&Self::Params>;
+
+    /// Check that the params describe a bet the game offers
+    fn validate(params: &Self::Params) -> Result<(), String>;
+
+    /// Total staked by params that split the bet between several positions, which the bet
+    /// amount must match
+    fn staked(_params: &Self::Params) -> Option<Amount> {
+        None
+    }
+
+    /// Highest multiplier (* 100) a bet with these params can be paid at the current algorithm version
+    fn max_multiplier(params: &Self::Params) -> u32;
+
+    /// Draw the outcome with a sampling algorithm version, `None` if the version is unknown
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &Self::Params) -> Option<Self::Outcome>;
+
+    /// Multiplier (* 100) the bet is paid at
+    fn multiplier(params: &Self::Params, outcome: &Self::Outcome) -> u32;
+
+    /// Exact payout of params that stake several positions themselves, which paying the bet
+    /// amount at the multiplier would round
+    fn payout(_params: &Self::Params, _outcome: &Self::Outcome) -> Option<Amount> {
+        None
+    }
+
+    /// The outcome as recorded in the game history, which verifiers compare against
+    fn serialize_outcome(params: &Self::Params, outcome: &Self::Outcome) -> String;
+
+    /// The outcome in the structured form clients render it from, for games that have one
+    fn result(_params: &Self::Params, _outcome: &Self::Outcome) -> GameResult {
+        GameResult::default()
+    }
+
+    /// Bind the house edge a bet is placed at into its params, so the outcome replays without
+    /// the casino's configuration
+    fn set_house_edge(_game_params: &mut GameParams, _house_edge_bps: u32) {}
+
+    /// Params of outcomes settled before params were typed. Returns `None` for params the
+    /// typed ones can't reproduce exactly.
+    fn legacy_params(game_params: &str) -> Option<GameParams>;
+}
+
+/// A registered [`Game`], working on the [`GameParams`] of any game
+pub trait GameHandler: Sync {
+    fn game_type(&self) -> GameType;
+
+    /// Whether the game is played over several operations after the bet
+    fn is_interactive(&self) -> bool;
+
+    /// House edge (basis points) used until the operator configures one, if the game has one
+    fn default_house_edge(&self) -> Option<u32>;
+
+    /// Bind the house edge a bet is placed at into its params
+    fn set_house_edge(&self, game_params: &mut GameParams, house_edge_bps: u32);
+
+    /// Check that the params describe a bet the game offers
+    fn validate(&self, game_params: &GameParams) -> Result<(), CasinoError>;
+
+    /// Check that params staking their own positions add up to the bet amount
+    fn check_stake(&self, game_params: &GameParams, bet_amount: Amount) -> Result<(), CasinoError>;
+
+    /// Highest multiplier (* 100) a bet with these params can be paid at the current algorithm version
+    fn max_multiplier(&self, game_params: &GameParams) -> u32;
+
+    /// Calculate the outcome with the sampling algorithm version it is settled with, or `None`
+    /// for unknown versions
+    fn calculate_outcome(&self, algorithm_version: u8, entropy: &[u8; 32], game_params: &GameParams) -> Option<SettledOutcome>;
+
+    /// Params of outcomes settled before params were typed
+    fn legacy_params(&self, game_params: &str) -> Option<GameParams>;
+}
+
+/// What a bet is paid and recorded with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettledOutcome {
+    /// The outcome string of the game history
+    pub details: String,
+    /// Multiplier (* 100) the bet is paid at
+    pub multiplier: u32,
+    pub result: GameResult,
+    /// Payout of params staking their own positions, instead of the bet amount at `multiplier`
+    pub exact_payout: Option<Amount>,
+}
+
+impl SettledOutcome {
+    /// What a bet of `bet_amount` is paid
+    pub fn payout(&self, bet_amount: Amount) -> Amount {
+        self.exact_payout
+            .unwrap_or_else(|| Amount::from_attos(bet_amount.to_attos() * self.multiplier as u128 / 100))
+    }
+}
+
+struct Registered<G>(PhantomData<fn() -> G>);
+
+impl<G: Game> Registered<G> {
+    fn params<'a>(&self, game_params: &'a GameParams) -> &'a G::Params {
+        G::params(game_params).expect("Params dispatched to another game")
+    }
+}
+
+impl<G: Game> GameHandler for Registered<G> {
+    fn game_type(&self) -> GameType {
+        G::GAME_TYPE
+    }
+
+    fn is_interactive(&self) -> bool {
+        G::INTERACTIVE
+    }
+
+    fn default_house_edge(&self) -> Option<u32> {
+        G::DEFAULT_HOUSE_EDGE_BPS
+    }
+
+    fn set_house_edge(&self, game_params: &mut GameParams, house_edge_bps: u32) {
+        G::set_house_edge(game_params, house_edge_bps)
+    }
+
+    fn validate(&self, game_params: &GameParams) -> Result<(), CasinoError> {
+        G::validate(self.params(game_params)).map_err(CasinoError::InvalidGameParams)
+    }
+
+    fn check_stake(&self, game_params: &GameParams, bet_amount: Amount) -> Result<(), CasinoError> {
+        match G::staked(self.params(game_params)) {
+            Some(staked) if staked != bet_amount => Err(CasinoError::InvalidGameParams(format!(
+                "The positions stake {} in total, not the bet amount {}",
+                staked, bet_amount
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn max_multiplier(&self, game_params: &GameParams) -> u32 {
+        G::max_multiplier(self.params(game_params))
+    }
+
+    fn calculate_outcome(&self, algorithm_version: u8, entropy: &[u8; 32], game_params: &GameParams) -> Option<SettledOutcome> {
+        let params = self.params(game_params);
+        let outcome = G::calculate_outcome(algorithm_version, entropy, params)?;
+        Some(SettledOutcome {
+            details: G::serialize_outcome(params, &outcome),
+            multiplier: G::multiplier(params, &outcome),
+            result: G::result(params, &outcome),
+            exact_payout: G::payout(params, &outcome),
+        })
+    }
+
+    fn legacy_params(&self, game_params: &str) -> Option<GameParams> {
+        G::legacy_params(game_params)
+    }
+}
+
+static GAMES: [&dyn GameHandler; 9] = [
+    &Registered::<roulette::Roulette>(PhantomData),
+    &Registered::<plinko::Plinko>(PhantomData),
+    &Registered::<mines::Mines>(PhantomData),
+    &Registered::<wheel::Wheel>(PhantomData),
+    &Registered::<dice::Dice>(PhantomData),
+    &Registered::<limbo::Limbo>(PhantomData),
+    &Registered::<crash::Crash>(PhantomData),
+    &Registered::<blackjack::Blackjack>(PhantomData),
+    &Registered::<baccarat::Baccarat>(PhantomData),
+];
+
+/// Every game the casino offers
+pub fn registry() -> &'static [&'static dyn GameHandler] {
+    &GAMES
+}
+
+/// The registered game of a type
+pub fn game(game_type: GameType) -> &'static dyn GameHandler {
+    *GAMES.iter()
+        .find(|game| game.game_type() == game_type)
+        .expect("Every game type is registered")
+}
+
+/// The game a bet's params are for
+pub fn game_for(game_params: &GameParams) -> &'static dyn GameHandler {
+    game(game_params.game_type())
+}
+
+/// Params as an outcome records them: the JSON of its `GameParams`
+pub fn params_json(game_params: &GameParams) -> String {
+    async_graphql::to_value(game_params)
+        .ok()
+        .and_then(|value| value.into_json().ok())
+        .expect("Game params serialize to JSON")
+        .to_string()
+}
+
+/// Params of a recorded outcome: the JSON of its `GameParams`, or for outcomes settled before
+/// params were typed, the game's old string format. Returns `None` for legacy params the
+/// typed ones can't reproduce exactly.
+#[cfg(any(test, feature = "verify"))]
+pub fn recorded_params(game_type: GameType, game_params: &str) -> Option<GameParams> {
+    if let Ok(params) = serde_json::from_str::<GameParams>(game_params) {
+        return (params.game_type() == game_type).then_some(params);
+    }
+    game(game_type).legacy_params(game_params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BaccaratBet, BaccaratParams, BlackjackParams, BlackjackPayout, BlackjackRules, CrashParams, DiceDirection, DiceParams, LimboParams, MinesParams, PlinkoParams, RiskLevel, RouletteBet, RouletteChip, RouletteColor, RouletteHalf, RouletteParams,
+        WheelParams,
+    };
+    use entropy::ALGORITHM_VERSION;
+
+    /// Bets every registered game is checked with
+    fn sample_bets() -> Vec<GameParams> {
+        vec![
+            GameParams::Roulette(RouletteParams { bet: Some(RouletteBet::Straight(0)), chips: Vec::new() }),
+            GameParams::Roulette(RouletteParams { bet: Some(RouletteBet::Color(RouletteColor::Black)), chips: Vec::new() }),
+            GameParams::Roulette(RouletteParams {
+                bet: None,
+                chips: vec![
+                    RouletteChip { bet: RouletteBet::Corner(vec![0, 1, 2, 3]), amount: Amount::from_attos(7) },
+                    RouletteChip { bet: RouletteBet::HighLow(RouletteHalf::High), amount: Amount::from_attos(3) },
+                ],
+            }),
+            GameParams::Plinko(PlinkoParams { rows: 8, risk: RiskLevel::Low }),
+            GameParams::Plinko(PlinkoParams { rows: 12, risk: RiskLevel::Medium }),
+            GameParams::Plinko(PlinkoParams { rows: 16, risk: RiskLevel::High }),
+            GameParams::Mines(MinesParams { grid_size: 5, mines: 1, house_edge_bps: 300, picks: Vec::new() }),
+            GameParams::Mines(MinesParams { grid_size: 3, mines: 8, house_edge_bps: 0, picks: Vec::new() }),
+            GameParams::Mines(MinesParams { grid_size: 8, mines: 32, house_edge_bps: 100, picks: Vec::new() }),
+            GameParams::Wheel(WheelParams { segments: 10, risk: RiskLevel::Low }),
+            GameParams::Wheel(WheelParams { segments: 40, risk: RiskLevel::High }),
+            GameParams::Dice(DiceParams { target: 1, direction: DiceDirection::Under, house_edge_bps: 100 }),
+            GameParams::Dice(DiceParams { target: 5_000, direction: DiceDirection::Over, house_edge_bps: 0 }),
+            GameParams::Limbo(LimboParams { target: 101, house_edge_bps: 100 }),
+            GameParams::Limbo(LimboParams { target: MAX_MULTIPLIER, house_edge_bps: 500 }),
+            GameParams::Crash(CrashParams { auto_cash_out: None, cashed_out_at: None, house_edge_bps: 100 }),
+            GameParams::Crash(CrashParams { auto_cash_out: Some(250), cashed_out_at: None, house_edge_bps: 0 }),
+            GameParams::Blackjack(BlackjackParams::default()),
+            GameParams::Blackjack(BlackjackParams {
+                rules: BlackjackRules { dealer_hits_soft_17: true, blackjack_payout: BlackjackPayout::SixToFive },
+                stake: Amount::from_attos(10),
+                actions: Vec::new(),
+            }),
+            GameParams::Baccarat(BaccaratParams { bet: BaccaratBet::Banker, amount: None, player_pair: None, banker_pair: None }),
+            GameParams::Baccarat(BaccaratParams {
+                bet: BaccaratBet::Tie,
+                amount: Some(Amount::from_attos(100)),
+                player_pair: Some(Amount::from_attos(10)),
+                banker_pair: Some(Amount::from_attos(10)),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_every_game_type_registered_once() {
+        for game in registry() {
+            let matching = registry().iter().filter(|other| other.game_type() == game.game_type()).count();
+            assert_eq!(matching, 1, "{:?} registered {} times", game.game_type(), matching);
+        }
+        for params in sample_bets() {
+            assert_eq!(game_for(&params).game_type(), params.game_type());
+        }
+    }
+
+    #[test]
+    fn test_games_share_settlement_rules() {
+        for params in sample_bets() {
+            let game = game_for(&params);
+            assert_eq!(game.validate(&params), Ok(()), "{:?}", params);
+            assert_eq!(game.calculate_outcome(0, &[1; 32], &params), None);
+
+            let max = game.max_multiplier(&params);
+            for seed in 0..100u8 {
+                let entropy = [seed; 32];
+                let settled = game.calculate_outcome(ALGORITHM_VERSION, &entropy, &params).unwrap();
+                assert_eq!(game.calculate_outcome(ALGORITHM_VERSION, &entropy, &params), Some(settled.clone()));
+                assert!(settled.multiplier <= max, "{:?} paid {} over {}", params, settled.multiplier, max);
+            }
+
+            // Recorded params replay the same bet
+            let json = params_json(&params);
+            assert_eq!(serde_json::from_str::<serde_json::Value>(&json).unwrap(), serde_json::to_value(&params).unwrap());
+            assert_eq!(recorded_params(game.game_type(), &json), Some(params));
+        }
+    }
+}
@@ -1,55 +1,187 @@
 // Copyright (c) APT Casino. All rights reserved.
 // Plinko game logic
 
-use sha3::{Digest, Sha3_256};
+use super::{
+    entropy::{legacy_hash, EntropyStream},
+    Game,
+};
+use crate::{GameParams, GameResult, GameType, PlinkoParams, PlinkoResult, RiskLevel};
 
-/// Plinko multipliers for different landing positions (16 rows, 17 positions)
-const PLINKO_MULTIPLIERS: [u32; 17] = [
+/// Multipliers of the single 17-slot table algorithm v1 and v2 paid every board on
+const LEGACY_MULTIPLIERS: [u32; 17] = [
     1000, 500, 300, 200, 150, 120, 110, 105, 100, 105, 110, 120, 150, 200, 300, 500, 1000
 ];
 
-/// Calculate plinko outcome from reveal value
-/// Returns (outcome_string, multiplier * 100)
-pub fn calculate_outcome(reveal_value: &[u8; 32], game_params: &str) -> (String, u32) {
-    // Parse rows from game_params, default to 16
-    let rows: u32 = game_params.parse().unwrap_or(16);
-    let rows = rows.clamp(8, 16);
-    
-    // Generate the ball path
-    let mut hasher = Sha3_256::new();
-    hasher.update(reveal_value);
-    hasher.update(b"plinko");
-    let hash: [u8; 32] = hasher.finalize().into();
-    
-    // Simulate the ball falling through pegs
-    // Start at center position
-    let mut position: i32 = (rows as i32) / 2;
-    let mut path = Vec::new();
-    
-    for i in 0..rows {
-        let byte_idx = (i / 8) as usize;
-        let bit_idx = i % 8;
-        let go_right = if byte_idx < hash.len() {
-            (hash[byte_idx] >> bit_idx) & 1 == 1
-        } else {
-            false
-        };
-        
-        if go_right {
-            position += 1;
-            path.push('R');
-        } else {
-            position -= 1;
-            path.push('L');
+pub const MIN_ROWS: u8 = 8;
+pub const MAX_ROWS: u8 = 16;
+
+/// Return every payout table is calibrated to, in basis points of the stake. Each table pays
+/// between 98.90% and 99.00% over the binomial odds of its slots.
+pub const TARGET_RTP_BPS: u32 = 9_900;
+
+/// Multipliers (* 100) by slot for 8 to 16 rows, one table per risk level
+const LOW_RISK: [&[u32]; 9] = [
+    &[560, 210, 110, 100, 50, 100, 110, 210, 560],
+    &[560, 200, 160, 100, 70, 70, 100, 160, 200, 560],
+    &[888, 300, 140, 110, 100, 50, 100, 110, 140, 300, 888],
+    &[836, 300, 190, 130, 100, 70, 70, 100, 130, 190, 300, 836],
+    &[1000, 300, 160, 140, 110, 100, 50, 100, 110, 140, 160, 300, 1000],
+    &[810, 400, 300, 190, 120, 90, 70, 70, 90, 120, 190, 300, 400, 810],
+    &[708, 400, 190, 140, 130, 110, 100, 50, 100, 110, 130, 140, 190, 400, 708],
+    &[1486, 800, 300, 200, 150, 110, 100, 70, 70, 100, 110, 150, 200, 300, 800, 1486],
+    &[1600, 900, 200, 140, 140, 120, 110, 100, 50, 100, 110, 120, 140, 140, 200, 900, 1600],
+];
+
+const MEDIUM_RISK: [&[u32]; 9] = [
+    &[1300, 300, 130, 70, 40, 70, 130, 300, 1300],
+    &[1764, 400, 170, 90, 50, 50, 90, 170, 400, 1764],
+    &[2200, 500, 200, 140, 60, 40, 60, 140, 200, 500, 2200],
+    &[2376, 600, 300, 180, 70, 50, 50, 70, 180, 300, 600, 2376],
+    &[3300, 1100, 400, 200, 110, 60, 30, 60, 110, 200, 400, 1100, 3300],
+    &[4300, 1300, 600, 300, 130, 70, 40, 40, 70, 130, 300, 600, 1300, 4300],
+    &[5800, 1500, 700, 400, 190, 100, 50, 20, 50, 100, 190, 400, 700, 1500, 5800],
+    &[8800, 1800, 1100, 500, 300, 130, 50, 30, 30, 50, 130, 300, 500, 1100, 1800, 8800],
+    &[11000, 4100, 1000, 500, 300, 150, 100, 50, 30, 50, 100, 150, 300, 500, 1000, 4100, 11000],
+];
+
+const HIGH_RISK: [&[u32]; 9] = [
+    &[2892, 400, 150, 30, 20, 30, 150, 400, 2892],
+    &[4284, 700, 200, 60, 20, 20, 60, 200, 700, 4284],
+    &[7568, 1000, 300, 90, 30, 20, 30, 90, 300, 1000, 7568],
+    &[12000, 1385, 520, 140, 40, 20, 20, 40, 140, 520, 1385, 12000],
+    &[17000, 2380, 810, 200, 70, 20, 20, 20, 70, 200, 810, 2380, 17000],
+    &[26000, 3672, 1100, 400, 100, 20, 20, 20, 20, 100, 400, 1100, 3672, 26000],
+    &[42000, 5600, 1800, 500, 190, 30, 20, 20, 20, 30, 190, 500, 1800, 5600, 42000],
+    &[62000, 8271, 2700, 800, 300, 50, 20, 20, 20, 20, 50, 300, 800, 2700, 8271, 62000],
+    &[100000, 13000, 2600, 900, 400, 200, 20, 20, 20, 20, 20, 200, 400, 900, 2600, 13000, 100000],
+];
+
+pub struct Plinko;
+
+/// Where a ball landed, and the table it was paid on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlinkoDrop {
+    /// The ball's bounce at each row, `true` to the right
+    pub bounces: Vec<bool>,
+    /// Slot the ball landed in, counted from the left
+    pub slot: usize,
+    /// Multipliers (* 100) of every slot of the board
+    pub multipliers: &'static [u32],
+    /// Dropped by algorithm v1 or v2, whose outcomes name the slot a position
+    legacy: bool,
+}
+
+impl Game for Plinko {
+    const GAME_TYPE: GameType = GameType::Plinko;
+    type Params = PlinkoParams;
+    type Outcome = PlinkoDrop;
+
+    fn params(game_params: &GameParams) -> Option<&PlinkoParams> {
+        match game_params {
+            GameParams::Plinko(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the board has a supported number of rows
+    fn validate(params: &PlinkoParams) -> Result<(), String> {
+        if !(MIN_ROWS..=MAX_ROWS).contains(&params.rows) {
+            return Err(format!("Plinko boards have {} to {} rows, not {}", MIN_ROWS, MAX_ROWS, params.rows));
+        }
+        Ok(())
+    }
+
+    /// Multiplier of the outer slots, the highest of every table
+    fn max_multiplier(params: &PlinkoParams) -> u32 {
+        multiplier_table(params).iter().copied().max().unwrap_or(0)
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &PlinkoParams) -> Option<PlinkoDrop> {
+        let rows = params.rows as u32;
+        match algorithm_version {
+            // Generate the ball path, one fair bounce per row, and land in the slot of its
+            // right bounces on the table of the board
+            3 | 4 => {
+                let bounces = EntropyStream::new(entropy, b"plinko").bits(rows as usize);
+                Some(PlinkoDrop {
+                    slot: bounces.iter().filter(|&&go_right| go_right).count(),
+                    bounces,
+                    multipliers: multiplier_table(params),
+                    legacy: false,
+                })
+            }
+            // v2 and v1 (bits read from a single hash) paid the 17-slot table, kept to verify
+            // old results
+            1 | 2 => {
+                let bounces = if algorithm_version == 2 {
+                    EntropyStream::new(entropy, b"plinko").bits(rows as usize)
+                } else {
+                    let hash = legacy_hash(entropy, b"plinko");
+                    (0..rows).map(|i| (hash[(i / 8) as usize] >> (i % 8)) & 1 == 1).collect()
+                };
+                Some(PlinkoDrop {
+                    slot: legacy_position(rows, &bounces),
+                    bounces,
+                    multipliers: &LEGACY_MULTIPLIERS,
+                    legacy: true,
+                })
+            }
+            _ => None,
         }
     }
-    
+
+    fn multiplier(_params: &PlinkoParams, drop: &PlinkoDrop) -> u32 {
+        drop.multipliers[drop.slot]
+    }
+
+    fn serialize_outcome(_params: &PlinkoParams, drop: &PlinkoDrop) -> String {
+        let path: String = drop.bounces.iter().map(|&go_right| if go_right { 'R' } else { 'L' }).collect();
+        if drop.legacy {
+            return format!("Plinko: Position {}, Path: {}", drop.slot, path);
+        }
+        format!("Plinko: Slot {}, Path: {}, Multipliers: {:?}", drop.slot, path, drop.multipliers)
+    }
+
+    /// The path as one byte per row, for clients to animate the drop peg by peg
+    fn result(_params: &PlinkoParams, drop: &PlinkoDrop) -> GameResult {
+        GameResult {
+            plinko: Some(PlinkoResult {
+                path: drop.bounces.iter().map(|&go_right| go_right as u8).collect(),
+                slot: drop.slot as u8,
+            }),
+            ..GameResult::default()
+        }
+    }
+
+    /// Params recorded as a bare row count, 16 if missing
+    fn legacy_params(game_params: &str) -> Option<GameParams> {
+        let rows: u32 = game_params.parse().unwrap_or(16);
+        Some(GameParams::Plinko(PlinkoParams {
+            rows: rows.clamp(MIN_ROWS as u32, MAX_ROWS as u32) as u8,
+            risk: RiskLevel::default(),
+        }))
+    }
+}
+
+/// Multipliers (* 100) of each slot of a board, from the leftmost. Boards outside
+/// [`MIN_ROWS`]..=[`MAX_ROWS`] have no table.
+pub fn multiplier_table(params: &PlinkoParams) -> &'static [u32] {
+    let tables = match params.risk {
+        RiskLevel::Low => &LOW_RISK,
+        RiskLevel::Medium => &MEDIUM_RISK,
+        RiskLevel::High => &HIGH_RISK,
+    };
+    params.rows.checked_sub(MIN_ROWS)
+        .and_then(|index| tables.get(index as usize))
+        .copied()
+        .unwrap_or(&[])
+}
+
+/// Slot the v1 and v2 ball landed in after falling through the pegs from the center
+fn legacy_position(rows: u32, bounces: &[bool]) -> usize {
+    let drift: i32 = bounces.iter().map(|&go_right| if go_right { 1 } else { -1 }).sum();
+    let position = (rows as i32) / 2 + drift;
     // Normalize position to 0-16 range
     let final_position = ((position + rows as i32) / 2) as usize;
-    let final_position = final_position.min(16);
-    
-    let multiplier = PLINKO_MULTIPLIERS[final_position];
-    let path_str: String = path.into_iter().collect();
-    
-    (format!("Plinko: Position {}, Path: {}", final_position, path_str), multiplier)
+    final_position.min(16)
 }
@@ -1,114 +1,123 @@
-//! Commit-Reveal Randomness Implementation
+//! Two-Party Commit-Reveal Randomness
 //!
-//! This module implements a provably fair randomness scheme where:
-//! 1. Player commits to a hash of their secret
-//! 2. House commits to a hash of their secret  
-//! 3. Player reveals their secret (verified against commit)
-//! 4. House reveals their secret (verified against commit)
-//! 5. Final random = XOR of both secrets
-
-use sha2::{Sha256, Digest};
-
-/// Compute SHA256 hash of a secret and return as hex string
-pub fn compute_commit(secret: &[u8]) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(secret);
-    let result = hasher.finalize();
-    hex::encode(result)
+//! This module implements the provably fair randomness scheme used by the contract:
+//! 1. House publishes the tip of a hash chain of seeds before any bet is placed against it
+//! 2. Player places a bet committing to a hash of their own seed
+//! 3. Both sides reveal their seeds (player seed against its commit, house seed against
+//!    the previous link of the chain)
+//! 4. Final entropy = SHA3(player_seed XOR house_seed || game_id)
+//!
+//! Neither side can pick the outcome: the player does not know the house seed when
+//! committing, and the house seed was fixed before the player's commit existed.
+//!
+//! Session bets only need the house to reveal: the house commits the tip of a hash chain of
+//! server seeds per player, the player picks a client seed, and bet `n` is bound to link
+//! `n + 1`, its entropy being HMAC-SHA3-256(server_seed, "client_seed:nonce"). Each server
+//! seed is revealed to settle its bet and checked against the previous link.
+
+use sha3::{Digest, Sha3_256};
+
+/// Domain separator for the combined game entropy
+const ENTROPY_DOMAIN: &[u8] = b"apt-casino/entropy";
+
+/// SHA3-256 rate in bytes, the HMAC block size
+const SHA3_256_BLOCK_SIZE: usize = 136;
+
+/// Compute the SHA3-256 commitment of a seed
+pub fn compute_commit(seed: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(seed);
+    hasher.finalize().into()
 }
 
-/// Verify that a revealed secret matches its commitment
-pub fn verify_commit(secret_hex: &str, commit_hex: &str) -> Result<bool, String> {
-    let secret = hex::decode(secret_hex).map_err(|e| format!("Invalid secret hex: {}", e))?;
-    let expected_commit = compute_commit(&secret);
-    Ok(expected_commit.to_lowercase() == commit_hex.to_lowercase())
+/// Verify that a revealed seed matches its commitment
+pub fn verify_commit(seed: &[u8; 32], commit: &[u8; 32]) -> bool {
+    compute_commit(seed) == *commit
 }
 
-/// Combine player and house secrets using XOR to produce final randomness
-pub fn compute_random(player_secret_hex: &str, house_secret_hex: &str) -> Result<u64, String> {
-    let player_secret = hex::decode(player_secret_hex)
-        .map_err(|e| format!("Invalid player secret hex: {}", e))?;
-    let house_secret = hex::decode(house_secret_hex)
-        .map_err(|e| format!("Invalid house secret hex: {}", e))?;
-    
+/// Combine player and house seeds into the entropy a game is settled with
+pub fn combine_seeds(player_seed: &[u8; 32], house_seed: &[u8; 32], game_id: u64) -> [u8; 32] {
     // XOR the secrets
-    let mut combined = vec![0u8; player_secret.len().max(house_secret.len())];
+    let mut combined = [0u8; 32];
     for (i, byte) in combined.iter_mut().enumerate() {
-        let p = player_secret.get(i).copied().unwrap_or(0);
-        let h = house_secret.get(i).copied().unwrap_or(0);
-        *byte = p ^ h;
+        *byte = player_seed[i] ^ house_seed[i];
     }
-    
-    // Hash the XOR'd result for better distribution
-    let mut hasher = Sha256::new();
-    hasher.update(&combined);
-    let hash = hasher.finalize();
-    
-    // Take first 8 bytes as u64
-    let bytes: [u8; 8] = hash[..8].try_into().unwrap();
-    Ok(u64::from_le_bytes(bytes))
+
+    // Hash with the game id so identical seed pairs never repeat an outcome
+    let mut hasher = Sha3_256::new();
+    hasher.update(ENTROPY_DOMAIN);
+    hasher.update(combined);
+    hasher.update(game_id.to_be_bytes());
+    hasher.finalize().into()
 }
 
-/// Generate a random value in a range [0, max)
-pub fn random_in_range(random: u64, max: u64) -> u64 {
-    if max == 0 {
-        return 0;
+/// HMAC-SHA3-256 (RFC 2104)
+pub fn hmac_sha3(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block_key = [0u8; SHA3_256_BLOCK_SIZE];
+    if key.len() > SHA3_256_BLOCK_SIZE {
+        block_key[..32].copy_from_slice(&Sha3_256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
     }
-    random % max
+
+    let mut inner = Sha3_256::new();
+    inner.update(block_key.map(|b| b ^ 0x36));
+    inner.update(message);
+    let inner_hash = inner.finalize();
+
+    let mut outer = Sha3_256::new();
+    outer.update(block_key.map(|b| b ^ 0x5c));
+    outer.update(inner_hash);
+    outer.finalize().into()
 }
 
-/// Generate multiple random values from a single seed
-pub fn expand_random(seed: u64, count: usize) -> Vec<u64> {
-    let mut results = Vec::with_capacity(count);
-    let mut current = seed;
-    
-    for i in 0..count {
-        // Use a simple mixing function to generate more values
-        let mut hasher = Sha256::new();
-        hasher.update(current.to_le_bytes());
-        hasher.update((i as u64).to_le_bytes());
-        let hash = hasher.finalize();
-        
-        let bytes: [u8; 8] = hash[..8].try_into().unwrap();
-        current = u64::from_le_bytes(bytes);
-        results.push(current);
-    }
-    
-    results
+/// Entropy of a session bet: HMAC(server_seed, "client_seed:nonce")
+pub fn session_entropy(server_seed: &[u8; 32], client_seed: &str, nonce: u64) -> [u8; 32] {
+    hmac_sha3(server_seed, format!("{}:{}", client_seed, nonce).as_bytes())
+}
+
+/// Entropy of a crash round, shared by every bet on it: HMAC(round_seed, "crash:round_id")
+pub fn round_entropy(round_seed: &[u8; 32], round_id: u64) -> [u8; 32] {
+    hmac_sha3(round_seed, format!("crash:{}", round_id).as_bytes())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_commit_verify() {
-        let secret = b"test_secret_12345678901234567890";
-        let secret_hex = hex::encode(secret);
-        let commit = compute_commit(secret);
-        
-        assert!(verify_commit(&secret_hex, &commit).unwrap());
-        assert!(!verify_commit("wrong_secret", &commit).unwrap_or(false));
+        let seed = [7u8; 32];
+        let commit = compute_commit(&seed);
+
+        assert!(verify_commit(&seed, &commit));
+        assert!(!verify_commit(&[8u8; 32], &commit));
     }
-    
+
     #[test]
-    fn test_compute_random() {
-        let player = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
-        let house = "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210";
-        
-        let random = compute_random(player, house).unwrap();
-        assert!(random > 0);
-        
+    fn test_combine_seeds() {
+        let player = [0x11u8; 32];
+        let house = [0x22u8; 32];
+
+        let entropy = combine_seeds(&player, &house, 1);
         // Same inputs should produce same output
-        let random2 = compute_random(player, house).unwrap();
-        assert_eq!(random, random2);
+        assert_eq!(entropy, combine_seeds(&player, &house, 1));
+        // Either seed alone changes the result
+        assert_ne!(entropy, combine_seeds(&[0x12u8; 32], &house, 1));
+        assert_ne!(entropy, combine_seeds(&player, &[0x23u8; 32], 1));
+        // The game id is mixed in
+        assert_ne!(entropy, combine_seeds(&player, &house, 2));
     }
-    
+
     #[test]
-    fn test_random_in_range() {
-        let random = 12345678901234567890u64;
-        let result = random_in_range(random, 37);
-        assert!(result < 37);
+    fn test_session_entropy() {
+        let server_seed = [0x33u8; 32];
+
+        let entropy = session_entropy(&server_seed, "lucky", 0);
+        assert_eq!(entropy, session_entropy(&server_seed, "lucky", 0));
+        // Every nonce gives a fresh result
+        assert_ne!(entropy, session_entropy(&server_seed, "lucky", 1));
+        assert_ne!(entropy, session_entropy(&server_seed, "unlucky", 0));
+        assert_ne!(entropy, session_entropy(&[0x34u8; 32], "lucky", 0));
     }
 }
-
@@ -1,78 +1,305 @@
 // Copyright (c) APT Casino. All rights reserved.
 // Roulette game logic
 
-use sha3::{Digest, Sha3_256};
-
-/// Calculate roulette outcome from reveal value
-/// Returns (outcome_string, multiplier * 100)
-pub fn calculate_outcome(reveal_value: &[u8; 32], game_params: &str) -> (String, u32) {
-    // Generate random number 0-36
-    let mut hasher = Sha3_256::new();
-    hasher.update(reveal_value);
-    hasher.update(b"roulette");
-    let hash: [u8; 32] = hasher.finalize().into();
-    
-    let random_u32 = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
-    let result = random_u32 % 37; // 0-36
-
-    // Parse the bet type from game_params
-    // Format: "bet_type:value" e.g., "number:17" or "color:red" or "odd_even:odd"
-    let parts: Vec<&str> = game_params.split(':').collect();
-    let (bet_type, bet_value) = if parts.len() >= 2 {
-        (parts[0], parts[1])
-    } else {
-        ("straight", "0")
-    };
-
-    let multiplier = match bet_type {
-        "number" | "straight" => {
-            // Straight bet on a single number - pays 35:1
-            if let Ok(bet_num) = bet_value.parse::<u32>() {
-                if result == bet_num { 3600 } else { 0 }
-            } else { 0 }
+use linera_sdk::linera_base_types::Amount;
+
+use super::{
+    entropy::{legacy_hash, EntropyStream},
+    Game,
+};
+use crate::{
+    GameParams, GameResult, GameType, RouletteBet, RouletteChip, RouletteChipResult, RouletteColor, RouletteHalf,
+    RouletteParams, RouletteParity, RouletteResult,
+};
+
+/// Most chips a single spin can carry
+pub const MAX_CHIPS: usize = 64;
+
+pub struct Roulette;
+
+impl Game for Roulette {
+    const GAME_TYPE: GameType = GameType::Roulette;
+    type Params = RouletteParams;
+    /// The winning number, 0 to 36
+    type Outcome = u32;
+
+    fn params(game_params: &GameParams) -> Option<&RouletteParams> {
+        match game_params {
+            GameParams::Roulette(params) => Some(params),
+            _ => None,
         }
-        "color" => {
-            // Red/Black bet - pays 1:1
-            let is_red = matches!(result, 1 | 3 | 5 | 7 | 9 | 12 | 14 | 16 | 18 | 19 | 21 | 23 | 25 | 27 | 30 | 32 | 34 | 36);
-            let bet_red = bet_value == "red";
-            if result == 0 {
-                0 // House wins on 0
-            } else if (is_red && bet_red) || (!is_red && !bet_red) {
-                200 // 2x payout
-            } else {
-                0
+    }
+
+    /// Check that the spin has a single bet or some chips, on positions that exist on the layout
+    fn validate(params: &RouletteParams) -> Result<(), String> {
+        match (&params.bet, params.chips.as_slice()) {
+            (Some(bet), []) => validate_bet(bet),
+            (None, []) => Err("A spin needs a bet or some chips".to_string()),
+            (None, chips) if chips.len() > MAX_CHIPS => {
+                Err(format!("A spin takes at most {} chips, not {}", MAX_CHIPS, chips.len()))
             }
+            (None, chips) => chips.iter().try_for_each(|chip| {
+                if chip.amount == Amount::ZERO {
+                    return Err(format!("The chip on {} stakes nothing", bet_label(&chip.bet)));
+                }
+                validate_bet(&chip.bet)
+            }),
+            (Some(_), _) => Err("A spin takes either a single bet or chips, not both".to_string()),
         }
-        "odd_even" => {
-            // Odd/Even bet - pays 1:1
-            if result == 0 {
-                0 // House wins on 0
-            } else {
-                let is_even = result % 2 == 0;
-                let bet_even = bet_value == "even";
-                if (is_even && bet_even) || (!is_even && !bet_even) {
-                    200 // 2x payout
-                } else {
-                    0
+    }
+
+    /// What the chips stake together
+    fn staked(params: &RouletteParams) -> Option<Amount> {
+        if params.chips.is_empty() {
+            return None;
+        }
+        Some(params.chips.iter().fold(Amount::ZERO, |total, chip| total.saturating_add(chip.amount)))
+    }
+
+    /// Highest multiplier of the whole stake, rounded up so the liability reserved for chips
+    /// covers what they can pay
+    fn max_multiplier(params: &RouletteParams) -> u32 {
+        let Some(bet) = &params.bet else {
+            let staked = staked_attos(&params.chips);
+            if staked == 0 {
+                return 0;
+            }
+            let most_returned = (0..37).map(|result| returned(&params.chips, result)).max().unwrap_or(0);
+            return most_returned.div_ceil(staked) as u32;
+        };
+        (0..37).map(|result| bet_multiplier(bet, result)).max().unwrap_or(0)
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], _params: &RouletteParams) -> Option<u32> {
+        match algorithm_version {
+            // Generate random number 0-36
+            2..=4 => Some(EntropyStream::new(entropy, b"roulette").uniform(37)),
+            // v1 (modulo-biased draw), kept to verify old results
+            1 => {
+                let hash = legacy_hash(entropy, b"roulette");
+                let random_u32 = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+                Some(random_u32 % 37)
+            }
+            _ => None,
+        }
+    }
+
+    /// Pay the bet against the winning number. Chips are paid at what they return together
+    /// over their total stake, rounded down.
+    fn multiplier(params: &RouletteParams, result: &u32) -> u32 {
+        match &params.bet {
+            Some(bet) => bet_multiplier(bet, *result),
+            None => {
+                let staked = staked_attos(&params.chips);
+                if staked == 0 {
+                    return 0;
                 }
+                (returned(&params.chips, *result) / staked) as u32
             }
         }
-        "high_low" => {
-            // High (19-36) / Low (1-18) - pays 1:1
-            if result == 0 {
-                0
+    }
+
+    /// Each chip is paid on its own stake
+    fn payout(params: &RouletteParams, result: &u32) -> Option<Amount> {
+        if params.chips.is_empty() {
+            return None;
+        }
+        Some(chip_results(&params.chips, *result).iter().fold(Amount::ZERO, |total, chip| total.saturating_add(chip.payout)))
+    }
+
+    fn serialize_outcome(params: &RouletteParams, result: &u32) -> String {
+        if let Some(bet) = &params.bet {
+            return format!("Roulette: {}, Bet: {}", result, bet_label(bet));
+        }
+        let chips: Vec<String> = chip_results(&params.chips, *result)
+            .iter()
+            .map(|chip| format!("{} {} pays {}", chip.bet, chip.amount, chip.payout))
+            .collect();
+        format!("Roulette: {}, Chips: {}", result, chips.join(", "))
+    }
+
+    /// The winning number, and the payout of each chip
+    fn result(params: &RouletteParams, result: &u32) -> GameResult {
+        GameResult {
+            roulette: Some(RouletteResult { number: *result, chips: chip_results(&params.chips, *result) }),
+            ..GameResult::default()
+        }
+    }
+
+    /// Params recorded in the "bet_type:value" format (e.g. "number:17", "color:red",
+    /// "odd_even:odd"). Only the spellings the outcome string reproduces exactly are accepted.
+    fn legacy_params(game_params: &str) -> Option<GameParams> {
+        let (bet_type, bet_value) = game_params.split_once(':')?;
+        let bet = match (bet_type, bet_value) {
+            ("number", value) => {
+                let number: u8 = value.parse().ok()?;
+                if number.to_string() != value {
+                    return None;
+                }
+                RouletteBet::Straight(number)
+            }
+            ("color", "red") => RouletteBet::Color(RouletteColor::Red),
+            ("color", "black") => RouletteBet::Color(RouletteColor::Black),
+            ("odd_even", "odd") => RouletteBet::OddEven(RouletteParity::Odd),
+            ("odd_even", "even") => RouletteBet::OddEven(RouletteParity::Even),
+            ("high_low", "low") => RouletteBet::HighLow(RouletteHalf::Low),
+            ("high_low", "high") => RouletteBet::HighLow(RouletteHalf::High),
+            _ => return None,
+        };
+        Some(GameParams::Roulette(RouletteParams { bet: Some(bet), chips: Vec::new() }))
+    }
+}
+
+/// Check that a bet exists on the layout
+fn validate_bet(bet: &RouletteBet) -> Result<(), String> {
+    match bet {
+        RouletteBet::Straight(number) if *number > 36 => {
+            Err(format!("Straight bet on {}, numbers go from 0 to 36", number))
+        }
+        RouletteBet::Split(numbers) => validate_group(bet, numbers, 2),
+        RouletteBet::Street(numbers) => validate_group(bet, numbers, 3),
+        RouletteBet::Corner(numbers) => validate_group(bet, numbers, 4),
+        RouletteBet::SixLine(numbers) => validate_group(bet, numbers, 6),
+        RouletteBet::Dozen(third) | RouletteBet::Column(third) if !(1..=3).contains(third) => {
+            Err(format!("{} bets are on 1, 2 or 3, not {}", bet_name(bet), third))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Multiplier (* 100) of a bet against the winning number
+fn bet_multiplier(bet: &RouletteBet, result: u32) -> u32 {
+    let even_money = |wins: bool| if wins { 200 } else { 0 };
+    let two_to_one = |wins: bool| if wins { 300 } else { 0 };
+    match bet {
+        // Straight bet on a single number - pays 35:1
+        RouletteBet::Straight(number) => if result == *number as u32 { 3600 } else { 0 },
+        // Inside bets on several numbers - split 17:1, street 11:1, corner 8:1, six line 5:1
+        RouletteBet::Split(numbers)
+        | RouletteBet::Street(numbers)
+        | RouletteBet::Corner(numbers)
+        | RouletteBet::SixLine(numbers) => {
+            if numbers.iter().any(|&number| number as u32 == result) {
+                3600 / numbers.len() as u32
             } else {
-                let is_high = result >= 19;
-                let bet_high = bet_value == "high";
-                if (is_high && bet_high) || (!is_high && !bet_high) {
-                    200
-                } else {
-                    0
+                0
+            }
+        }
+        // Dozen and column bets - pay 2:1, house wins on 0
+        RouletteBet::Dozen(dozen) => two_to_one(result != 0 && (result - 1) / 12 + 1 == *dozen as u32),
+        RouletteBet::Column(column) => two_to_one(result != 0 && (result - 1) % 3 + 1 == *column as u32),
+        // Red/Black bet - pays 1:1, house wins on 0
+        RouletteBet::Color(RouletteColor::Red) => even_money(result != 0 && is_red(result)),
+        RouletteBet::Color(RouletteColor::Black) => even_money(result != 0 && !is_red(result)),
+        // Odd/Even bet - pays 1:1, house wins on 0
+        RouletteBet::OddEven(RouletteParity::Odd) => even_money(result % 2 == 1),
+        RouletteBet::OddEven(RouletteParity::Even) => even_money(result != 0 && result % 2 == 0),
+        // High (19-36) / Low (1-18) - pays 1:1
+        RouletteBet::HighLow(RouletteHalf::Low) => even_money((1..=18).contains(&result)),
+        RouletteBet::HighLow(RouletteHalf::High) => even_money(result >= 19),
+    }
+}
+
+/// A bet as outcome strings spell it, e.g. `split:17-20` or `color:red`
+fn bet_label(bet: &RouletteBet) -> String {
+    let numbers = |numbers: &[u8]| numbers.iter().map(u8::to_string).collect::<Vec<_>>().join("-");
+    match bet {
+        RouletteBet::Straight(number) => format!("number:{}", number),
+        RouletteBet::Split(group)
+        | RouletteBet::Street(group)
+        | RouletteBet::Corner(group)
+        | RouletteBet::SixLine(group) => format!("{}:{}", bet_name(bet), numbers(group)),
+        RouletteBet::Dozen(third) | RouletteBet::Column(third) => format!("{}:{}", bet_name(bet), third),
+        RouletteBet::Color(RouletteColor::Red) => "color:red".to_string(),
+        RouletteBet::Color(RouletteColor::Black) => "color:black".to_string(),
+        RouletteBet::OddEven(RouletteParity::Odd) => "odd_even:odd".to_string(),
+        RouletteBet::OddEven(RouletteParity::Even) => "odd_even:even".to_string(),
+        RouletteBet::HighLow(RouletteHalf::Low) => "high_low:low".to_string(),
+        RouletteBet::HighLow(RouletteHalf::High) => "high_low:high".to_string(),
+    }
+}
+
+/// What each chip pays against the winning number, each rounded down to the atto
+fn chip_results(chips: &[RouletteChip], result: u32) -> Vec<RouletteChipResult> {
+    chips.iter()
+        .map(|chip| {
+            let multiplier = bet_multiplier(&chip.bet, result);
+            RouletteChipResult {
+                bet: bet_label(&chip.bet),
+                amount: chip.amount,
+                multiplier,
+                payout: Amount::from_attos(chip.amount.to_attos() * multiplier as u128 / 100),
+            }
+        })
+        .collect()
+}
+
+fn staked_attos(chips: &[RouletteChip]) -> u128 {
+    chips.iter().map(|chip| chip.amount.to_attos()).fold(0, u128::saturating_add)
+}
+
+/// Sum of each chip's stake times its multiplier (* 100), in attos
+fn returned(chips: &[RouletteChip], result: u32) -> u128 {
+    chips.iter()
+        .map(|chip| chip.amount.to_attos().saturating_mul(bet_multiplier(&chip.bet, result) as u128))
+        .fold(0, u128::saturating_add)
+}
+
+/// Name of a bet in outcome strings
+fn bet_name(bet: &RouletteBet) -> &'static str {
+    match bet {
+        RouletteBet::Straight(_) => "number",
+        RouletteBet::Split(_) => "split",
+        RouletteBet::Street(_) => "street",
+        RouletteBet::Corner(_) => "corner",
+        RouletteBet::SixLine(_) => "six_line",
+        RouletteBet::Dozen(_) => "dozen",
+        RouletteBet::Column(_) => "column",
+        RouletteBet::Color(_) => "color",
+        RouletteBet::OddEven(_) => "odd_even",
+        RouletteBet::HighLow(_) => "high_low",
+    }
+}
+
+/// Check that an inside bet covers `size` numbers forming one of the groups of its kind, in
+/// any order
+fn validate_group(bet: &RouletteBet, numbers: &[u8], size: usize) -> Result<(), String> {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+    if numbers.len() != size || !inside_groups(size).contains(&sorted) {
+        return Err(format!("{:?} isn't a group of numbers a {} bet covers", numbers, bet_name(bet)));
+    }
+    Ok(())
+}
+
+/// Every group of `size` numbers an inside bet can cover on the single-zero layout, in
+/// increasing order. Rows are 1-2-3 to 34-35-36 and 0 sits above the first one.
+fn inside_groups(size: usize) -> Vec<Vec<u8>> {
+    let rows = (1..=34u8).step_by(3);
+    match size {
+        2 => {
+            let mut splits = vec![vec![0, 1], vec![0, 2], vec![0, 3]];
+            for number in 1..=36u8 {
+                if number % 3 != 0 {
+                    splits.push(vec![number, number + 1]);
+                }
+                if number <= 33 {
+                    splits.push(vec![number, number + 3]);
                 }
             }
+            splits
         }
-        _ => 0,
-    };
+        3 => [vec![0, 1, 2], vec![0, 2, 3]].into_iter()
+            .chain(rows.map(|first| vec![first, first + 1, first + 2]))
+            .collect(),
+        4 => std::iter::once(vec![0, 1, 2, 3])
+            .chain((1..=32u8).filter(|number| number % 3 != 0).map(|n| vec![n, n + 1, n + 3, n + 4]))
+            .collect(),
+        6 => rows.take(11).map(|first| (first..first + 6).collect()).collect(),
+        _ => Vec::new(),
+    }
+}
 
-    (format!("Roulette: {}, Bet: {}:{}", result, bet_type, bet_value), multiplier)
+fn is_red(number: u32) -> bool {
+    matches!(number, 1 | 3 | 5 | 7 | 9 | 12 | 14 | 16 | 18 | 19 | 21 | 23 | 25 | 27 | 30 | 32 | 34 | 36)
 }
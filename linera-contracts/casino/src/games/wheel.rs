@@ -1,10 +1,15 @@
 // Copyright (c) APT Casino. All rights reserved.
 // Spin Wheel game logic
 
-use sha3::{Digest, Sha3_256};
+use super::{
+    entropy::{legacy_hash, EntropyStream},
+    Game,
+};
+use crate::{GameParams, GameResult, GameType, RiskLevel, WheelColor, WheelParams, WheelResult, WheelSegment};
 
-/// Wheel segments with their multipliers (multiplier * 100)
-const WHEEL_SEGMENTS: [(u32, &str); 8] = [
+/// Segments of the 8-segment wheel algorithm v1 to v3 spun, with the labels its outcomes
+/// recorded (multiplier * 100)
+const LEGACY_SEGMENTS: [(u32, &str); 8] = [
     (200, "1x"),     // 2x - most common
     (150, "0.5x"),   // 1.5x
     (300, "2x"),     // 3x
@@ -15,22 +20,157 @@ const WHEEL_SEGMENTS: [(u32, &str); 8] = [
     (100, "0x"),     // Lose small (return bet)
 ];
 
-/// Calculate wheel outcome from reveal value
-/// Returns (outcome_string, multiplier * 100)
-pub fn calculate_outcome(reveal_value: &[u8; 32], _game_params: &str) -> (String, u32) {
-    let mut hasher = Sha3_256::new();
-    hasher.update(reveal_value);
-    hasher.update(b"wheel");
-    let hash: [u8; 32] = hasher.finalize().into();
-    
-    // Generate random segment (0-7)
-    let random_u32 = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
-    let segment = (random_u32 % 8) as usize;
-    
-    let (multiplier, label) = WHEEL_SEGMENTS[segment];
-    
-    // Calculate spin angle for visual display
-    let angle = (segment as u32 * 45) + (random_u32 % 45); // Add variance within segment
-    
-    (format!("Wheel: Segment {} ({}), Angle {}°", segment, label, angle), multiplier)
+/// Segment counts a wheel can be spun with
+pub const SEGMENT_COUNTS: [u8; 5] = [10, 20, 30, 40, 50];
+
+/// Return every layout pays, in basis points of the stake
+pub const TARGET_RTP_BPS: u32 = 9_900;
+
+/// Ten segments the low and medium risk wheels repeat all the way around, each block paying
+/// [`TARGET_RTP_BPS`] over its segments (multiplier * 100)
+const LOW_RISK_BLOCK: [u32; 10] = [150, 120, 120, 120, 0, 120, 120, 120, 120, 0];
+const MEDIUM_RISK_BLOCK: [u32; 10] = [0, 190, 0, 150, 0, 200, 0, 150, 0, 300];
+
+pub struct Wheel;
+
+/// Where the wheel stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WheelSpin {
+    /// A segment of the layout the bet was placed on
+    Layout { segment: u32, multiplier: u32, color: WheelColor },
+    /// A segment of the 8-segment wheel, with an offset within it for the visual spin angle
+    Legacy { segment: u32, offset: u32 },
+}
+
+impl Game for Wheel {
+    const GAME_TYPE: GameType = GameType::Wheel;
+    type Params = WheelParams;
+    type Outcome = WheelSpin;
+
+    fn params(game_params: &GameParams) -> Option<&WheelParams> {
+        match game_params {
+            GameParams::Wheel(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Check that the wheel layout exists
+    fn validate(params: &WheelParams) -> Result<(), String> {
+        if !SEGMENT_COUNTS.contains(&params.segments) {
+            return Err(format!("Wheels have {:?} segments, not {}", SEGMENT_COUNTS, params.segments));
+        }
+        Ok(())
+    }
+
+    /// Highest multiplier on the wheel
+    fn max_multiplier(params: &WheelParams) -> u32 {
+        layout(params).iter().map(|segment| segment.multiplier).max().unwrap_or(0)
+    }
+
+    fn calculate_outcome(algorithm_version: u8, entropy: &[u8; 32], params: &WheelParams) -> Option<WheelSpin> {
+        match algorithm_version {
+            // Stop on a uniformly drawn segment of the layout
+            4 => {
+                let layout = layout(params);
+                // Only segment counts validation rejects have no layout
+                if layout.is_empty() {
+                    return None;
+                }
+                let segment = EntropyStream::new(entropy, b"wheel").uniform(layout.len() as u32);
+                let WheelSegment { multiplier, color } = layout[segment as usize];
+                Some(WheelSpin::Layout { segment, multiplier, color })
+            }
+            // v2 and v3 spun the 8-segment wheel, then an offset within the segment for the
+            // visual spin angle
+            2 | 3 => {
+                let mut stream = EntropyStream::new(entropy, b"wheel");
+                let segment = stream.uniform(8);
+                let offset = stream.uniform(45);
+                Some(WheelSpin::Legacy { segment, offset })
+            }
+            // v1 (modulo-biased draw), kept to verify old results
+            1 => {
+                let hash = legacy_hash(entropy, b"wheel");
+                let random_u32 = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+                Some(WheelSpin::Legacy { segment: random_u32 % 8, offset: random_u32 % 45 })
+            }
+            _ => None,
+        }
+    }
+
+    fn multiplier(_params: &WheelParams, spin: &WheelSpin) -> u32 {
+        match spin {
+            WheelSpin::Layout { multiplier, .. } => *multiplier,
+            WheelSpin::Legacy { segment, .. } => LEGACY_SEGMENTS[*segment as usize].0,
+        }
+    }
+
+    fn serialize_outcome(params: &WheelParams, spin: &WheelSpin) -> String {
+        match spin {
+            WheelSpin::Layout { segment, multiplier, color } => format!(
+                "Wheel: Segment {} of {}, {}.{:02}x ({:?})",
+                segment,
+                params.segments,
+                multiplier / 100,
+                multiplier % 100,
+                color
+            ),
+            WheelSpin::Legacy { segment, offset } => {
+                let label = LEGACY_SEGMENTS[*segment as usize].1;
+                // Calculate spin angle for visual display
+                let angle = (segment * 45) + offset; // Add variance within segment
+                format!("Wheel: Segment {} ({}), Angle {}°", segment, label, angle)
+            }
+        }
+    }
+
+    /// The segment as the layout draws it
+    fn result(_params: &WheelParams, spin: &WheelSpin) -> GameResult {
+        match spin {
+            WheelSpin::Layout { segment, multiplier, color } => GameResult {
+                wheel: Some(WheelResult { segment: *segment, multiplier: *multiplier, color: *color }),
+                ..GameResult::default()
+            },
+            WheelSpin::Legacy { .. } => GameResult::default(),
+        }
+    }
+
+    /// Spins recorded before params were typed had none, and were all on the 8-segment wheel
+    fn legacy_params(_game_params: &str) -> Option<GameParams> {
+        Some(GameParams::Wheel(WheelParams { segments: LEGACY_SEGMENTS.len() as u8, risk: RiskLevel::default() }))
+    }
+}
+
+/// Segments of a wheel clockwise from the top, empty for segment counts that aren't offered.
+///
+/// Low and medium risk wheels repeat a ten-segment block; a high risk wheel has a single
+/// paying segment worth the return of the whole wheel.
+pub fn layout(params: &WheelParams) -> Vec<WheelSegment> {
+    if !SEGMENT_COUNTS.contains(&params.segments) {
+        return Vec::new();
+    }
+    let segments = params.segments as usize;
+    let multipliers: Vec<u32> = match params.risk {
+        RiskLevel::Low => LOW_RISK_BLOCK.iter().copied().cycle().take(segments).collect(),
+        RiskLevel::Medium => MEDIUM_RISK_BLOCK.iter().copied().cycle().take(segments).collect(),
+        RiskLevel::High => {
+            let jackpot = segments as u32 * TARGET_RTP_BPS / 100;
+            (0..segments).map(|index| if index == 0 { jackpot } else { 0 }).collect()
+        }
+    };
+    multipliers.into_iter()
+        .map(|multiplier| WheelSegment { multiplier, color: color(multiplier) })
+        .collect()
+}
+
+/// Color of a segment paying `multiplier` (* 100)
+fn color(multiplier: u32) -> WheelColor {
+    match multiplier {
+        0 => WheelColor::Gray,
+        1..=149 => WheelColor::Green,
+        150..=199 => WheelColor::Blue,
+        200..=299 => WheelColor::Purple,
+        300..=999 => WheelColor::Orange,
+        _ => WheelColor::Gold,
+    }
 }
@@ -3,12 +3,69 @@
 
 /*! ABI of the Casino Application */
 
+pub mod games;
+
+use std::collections::BTreeMap;
+
 use async_graphql::{Request, Response};
-use linera_sdk::linera_base_types::{ContractAbi, ServiceAbi, Amount, AccountOwner, Timestamp};
+use linera_sdk::linera_base_types::{ContractAbi, ServiceAbi, Account, Amount, AccountOwner, ApplicationId, Timestamp};
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
 
 pub struct CasinoAbi;
 
+/// Application parameters, fixed when the casino is created
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CasinoParameters {
+    /// Fungible token applications accepted as betting currencies besides the native token.
+    /// Balances, bets and payouts are always denominated in the asset they were made in.
+    pub tokens: Vec<ApplicationId>,
+}
+
+/// A currency the casino holds balances in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Asset {
+    /// The chain's native token
+    Native,
+    /// A fungible token application
+    Token(ApplicationId),
+}
+
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Asset::Native => write!(f, "native"),
+            Asset::Token(token) => write!(f, "{}", token),
+        }
+    }
+}
+
+impl std::str::FromStr for Asset {
+    type Err = String;
+
+    /// Parse `native` or a fungible token application ID
+    fn from_str(asset: &str) -> Result<Self, Self::Err> {
+        if asset.eq_ignore_ascii_case("native") {
+            return Ok(Asset::Native);
+        }
+        asset.parse()
+            .map(Asset::Token)
+            .map_err(|_| format!("Unknown asset: {}", asset))
+    }
+}
+
+/// Basis points in a whole, for bankroll shares
+pub const BASIS_POINTS: u32 = 10_000;
+
+/// Betting limits of an asset's table
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TableLimits {
+    pub min_bet: Amount,
+    pub max_bet: Amount,
+    /// Largest share of the free bankroll a single bet may put at risk, in basis points
+    pub max_exposure_bps: u32,
+}
+
 /// The types of games supported
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum GameType {
@@ -16,28 +73,534 @@ pub enum GameType {
     Plinko,
     Mines,
     Wheel,
+    Dice,
+    Limbo,
+    Crash,
+    Blackjack,
+    Baccarat,
+}
+
+impl std::str::FromStr for GameType {
+    type Err = String;
+
+    /// Parse a game type name, case-insensitively
+    fn from_str(game_type: &str) -> Result<Self, Self::Err> {
+        match game_type.to_lowercase().as_str() {
+            "roulette" => Ok(GameType::Roulette),
+            "plinko" => Ok(GameType::Plinko),
+            "mines" => Ok(GameType::Mines),
+            "wheel" => Ok(GameType::Wheel),
+            "dice" => Ok(GameType::Dice),
+            "limbo" => Ok(GameType::Limbo),
+            "crash" => Ok(GameType::Crash),
+            "blackjack" => Ok(GameType::Blackjack),
+            "baccarat" => Ok(GameType::Baccarat),
+            _ => Err(format!("Unknown game type: {}", game_type)),
+        }
+    }
+}
+
+/// Parameters of a bet; the variant selects the game it is placed on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::OneofObject)]
+pub enum GameParams {
+    Roulette(RouletteParams),
+    Plinko(PlinkoParams),
+    Mines(MinesParams),
+    Wheel(WheelParams),
+    Dice(DiceParams),
+    Limbo(LimboParams),
+    Crash(CrashParams),
+    Blackjack(BlackjackParams),
+    Baccarat(BaccaratParams),
+}
+
+impl GameParams {
+    /// The game these params are for
+    pub fn game_type(&self) -> GameType {
+        match self {
+            GameParams::Roulette(_) => GameType::Roulette,
+            GameParams::Plinko(_) => GameType::Plinko,
+            GameParams::Mines(_) => GameType::Mines,
+            GameParams::Wheel(_) => GameType::Wheel,
+            GameParams::Dice(_) => GameType::Dice,
+            GameParams::Limbo(_) => GameType::Limbo,
+            GameParams::Crash(_) => GameType::Crash,
+            GameParams::Blackjack(_) => GameType::Blackjack,
+            GameParams::Baccarat(_) => GameType::Baccarat,
+        }
+    }
+}
+
+/// A roulette spin, with either a single bet or chips on several positions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct RouletteParams {
+    /// A single position, staking the whole bet amount
+    #[graphql(default)]
+    #[serde(default)]
+    pub bet: Option<RouletteBet>,
+    /// Chips on several positions, all resolved against the same spin. Their amounts add up
+    /// to the bet amount.
+    #[graphql(default)]
+    #[serde(default)]
+    pub chips: Vec<RouletteChip>,
+}
+
+/// A stake on one position of the roulette layout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct RouletteChip {
+    pub bet: RouletteBet,
+    pub amount: Amount,
+}
+
+/// Where the chips are placed on the roulette layout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::OneofObject)]
+pub enum RouletteBet {
+    /// A single number, 0 to 36
+    Straight(u8),
+    /// Two adjacent numbers, including 0 with 1, 2 or 3
+    Split(Vec<u8>),
+    /// The three numbers of a row, or the baskets 0-1-2 and 0-2-3
+    Street(Vec<u8>),
+    /// Four numbers meeting at a corner, or the top line 0-1-2-3
+    Corner(Vec<u8>),
+    /// The six numbers of two adjacent rows
+    SixLine(Vec<u8>),
+    /// 1 for 1-12, 2 for 13-24, 3 for 25-36
+    Dozen(u8),
+    /// 1 for 1, 4, ... 34, 2 for 2, 5, ... 35, 3 for 3, 6, ... 36
+    Column(u8),
+    Color(RouletteColor),
+    OddEven(RouletteParity),
+    HighLow(RouletteHalf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum RouletteColor {
+    Red,
+    Black,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum RouletteParity {
+    Odd,
+    Even,
+}
+
+/// Low is 1 to 18, high is 19 to 36
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum RouletteHalf {
+    Low,
+    High,
+}
+
+/// A plinko drop
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct PlinkoParams {
+    /// Rows of pegs, 8 to 16
+    pub rows: u8,
+    /// Payout table of the board; riskier tables pay more on the edges and less in the middle
+    #[graphql(default)]
+    #[serde(default)]
+    pub risk: RiskLevel,
+}
+
+/// How a game's payout table spreads its return: riskier tables pay more on fewer outcomes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum RiskLevel {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+/// A mines round, played with `RevealTile` and `CashOut` after a session bet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct MinesParams {
+    /// Side of the square board, 3 to 8 cells
+    #[graphql(default = 5)]
+    #[serde(default = "default_grid_size")]
+    pub grid_size: u8,
+    /// Mines on the board, at least 1 and fewer than its cells
+    pub mines: u8,
+    /// House edge the round pays at, in basis points; set by the contract when the bet is placed
+    #[graphql(skip)]
+    #[serde(default = "default_mines_house_edge")]
+    pub house_edge_bps: u32,
+    /// Cells revealed so far, in order; empty when placing the bet
+    #[graphql(skip)]
+    pub picks: Vec<u8>,
+}
+
+// Rounds recorded before grid sizes and house edges were configurable were 5x5 at 3%
+fn default_grid_size() -> u8 {
+    5
+}
+
+fn default_mines_house_edge() -> u32 {
+    games::mines::DEFAULT_HOUSE_EDGE_BPS
+}
+
+/// A wheel spin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct WheelParams {
+    /// Segments on the wheel: 10, 20, 30, 40 or 50
+    #[graphql(default = 10)]
+    pub segments: u8,
+    /// Layout of the wheel; riskier wheels have fewer, bigger paying segments
+    #[graphql(default)]
+    #[serde(default)]
+    pub risk: RiskLevel,
+}
+
+/// Color a wheel segment is drawn in, by what it pays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum WheelColor {
+    /// Pays nothing
+    #[serde(alias = "GRAY")]
+    Gray,
+    /// Under 1.5x
+    #[serde(alias = "GREEN")]
+    Green,
+    /// 1.5x to under 2x
+    #[serde(alias = "BLUE")]
+    Blue,
+    /// 2x to under 3x
+    #[serde(alias = "PURPLE")]
+    Purple,
+    /// 3x to under 10x
+    #[serde(alias = "ORANGE")]
+    Orange,
+    /// 10x and over
+    #[serde(alias = "GOLD")]
+    Gold,
+}
+
+/// A dice roll, from 0.00 to 99.99
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct DiceParams {
+    /// Target in hundredths, 1 (0.01) to 9800 (98.00)
+    pub target: u32,
+    pub direction: DiceDirection,
+    /// House edge the roll pays at, in basis points; set by the contract when the bet is placed
+    #[graphql(skip)]
+    pub house_edge_bps: u32,
+}
+
+/// Whether a dice bet wins on rolls above or below its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum DiceDirection {
+    Over,
+    Under,
+}
+
+/// A limbo bet, won when the drawn multiplier reaches the target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct LimboParams {
+    /// Target multiplier (* 100), 101 (1.01x) to 100,000,000 (1,000,000x)
+    pub target: u32,
+    /// House edge the draw is made at, in basis points; set by the contract when the bet is placed
+    #[graphql(skip)]
+    pub house_edge_bps: u32,
+}
+
+/// A bet on a crash round, cashed out by hand while the round runs or at an automatic target
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct CrashParams {
+    /// Multiplier (* 100) to cash out at automatically, 101 (1.01x) to 100,000 (1,000x)
+    #[graphql(default)]
+    #[serde(default)]
+    pub auto_cash_out: Option<u32>,
+    /// Multiplier (* 100) of the curve when the player cashed out by hand; set by the contract
+    #[graphql(skip)]
+    #[serde(default)]
+    pub cashed_out_at: Option<u32>,
+    /// House edge of the round the bet is placed on, in basis points; set by the contract
+    #[graphql(skip)]
+    pub house_edge_bps: u32,
+}
+
+/// A blackjack hand, played with `PlayBlackjack` after a session bet
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct BlackjackParams {
+    /// Rules of the table, which must be the ones the operator set
+    #[graphql(default)]
+    #[serde(default)]
+    pub rules: BlackjackRules,
+    /// The initial bet, which doubles, splits and insurance stake on top of; set by the
+    /// contract when the bet is placed
+    #[graphql(skip)]
+    #[serde(default)]
+    pub stake: Amount,
+    /// Actions taken so far, in order; empty when placing the bet
+    #[graphql(skip)]
+    #[serde(default)]
+    pub actions: Vec<BlackjackAction>,
+}
+
+/// Rules the dealer plays and pays blackjacks by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject)]
+#[graphql(input_name = "BlackjackRulesInput")]
+pub struct BlackjackRules {
+    /// Whether the dealer hits soft 17 (H17) instead of standing on it (S17)
+    #[graphql(default)]
+    pub dealer_hits_soft_17: bool,
+    #[graphql(default)]
+    pub blackjack_payout: BlackjackPayout,
+}
+
+/// What a blackjack pays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum BlackjackPayout {
+    #[default]
+    ThreeToTwo,
+    SixToFive,
+}
+
+/// A move in a blackjack hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum BlackjackAction {
+    #[serde(alias = "HIT")]
+    Hit,
+    #[serde(alias = "STAND")]
+    Stand,
+    /// Double the hand's stake and take exactly one more card
+    #[serde(alias = "DOUBLE")]
+    Double,
+    /// Split a pair into two hands, staking another bet on the second
+    #[serde(alias = "SPLIT")]
+    Split,
+    /// Stake half a bet on the dealer's ace being a blackjack, paying 2:1
+    #[serde(alias = "INSURANCE")]
+    Insurance,
+    #[serde(alias = "DECLINE_INSURANCE")]
+    DeclineInsurance,
+}
+
+/// A baccarat coup, with optional pair side bets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct BaccaratParams {
+    /// Hand the main bet backs
+    pub bet: BaccaratBet,
+    /// Stake of the main bet, required with side bets. The stakes then add up to the bet amount.
+    #[graphql(default)]
+    #[serde(default)]
+    pub amount: Option<Amount>,
+    /// Side bet on the player's first two cards being a pair, paying 11:1
+    #[graphql(default)]
+    #[serde(default)]
+    pub player_pair: Option<Amount>,
+    /// Side bet on the banker's first two cards being a pair, paying 11:1
+    #[graphql(default)]
+    #[serde(default)]
+    pub banker_pair: Option<Amount>,
+}
+
+/// Side of a baccarat coup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum BaccaratBet {
+    /// Pays even money
+    #[serde(alias = "PLAYER")]
+    Player,
+    /// Pays even money less 5% commission
+    #[serde(alias = "BANKER")]
+    Banker,
+    /// Pays 8:1; bets on either side push on a tie
+    #[serde(alias = "TIE")]
+    Tie,
+}
+
+/// A card of a standard deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct PlayingCard {
+    /// 1 (ace) to 13 (king)
+    pub rank: u8,
+    pub suit: CardSuit,
+}
+
+impl PlayingCard {
+    /// The card at `index` (0 to 51) of a deck ordered by suit, then by rank from the ace
+    pub fn from_index(index: u8) -> Self {
+        let suit = match index / 13 % 4 {
+            0 => CardSuit::Clubs,
+            1 => CardSuit::Diamonds,
+            2 => CardSuit::Hearts,
+            _ => CardSuit::Spades,
+        };
+        PlayingCard { rank: index % 13 + 1, suit }
+    }
+}
+
+impl std::fmt::Display for PlayingCard {
+    /// Rank then suit initial, e.g. `AS`, `10H` or `QD`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rank = match self.rank {
+            1 => "A".to_string(),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            rank => rank.to_string(),
+        };
+        let suit = match self.suit {
+            CardSuit::Clubs => 'C',
+            CardSuit::Diamonds => 'D',
+            CardSuit::Hearts => 'H',
+            CardSuit::Spades => 'S',
+        };
+        write!(f, "{}{}", rank, suit)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum CardSuit {
+    #[serde(alias = "CLUBS")]
+    Clubs,
+    #[serde(alias = "DIAMONDS")]
+    Diamonds,
+    #[serde(alias = "HEARTS")]
+    Hearts,
+    #[serde(alias = "SPADES")]
+    Spades,
+}
+
+/// A segment of a wheel layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct WheelSegment {
+    /// Multiplier (* 100)
+    pub multiplier: u32,
+    pub color: WheelColor,
 }
 
 /// Operations that can be performed on the casino contract
 #[derive(Debug, Serialize, Deserialize)]
 pub enum CasinoOperation {
-    /// Deposit funds into the casino
-    Deposit { amount: Amount },
-    /// Withdraw funds from the casino
-    Withdraw { amount: Amount },
+    /// Deposit funds into the casino, transferring the asset from the signer's account
+    Deposit { asset: Asset, amount: Amount },
+    /// Withdraw funds from the casino, transferring the asset to `destination`
+    Withdraw {
+        asset: Asset,
+        amount: Amount,
+        destination: Account,
+    },
     /// Place a bet with a commit hash for the reveal phase
     PlaceBet {
-        game_type: GameType,
+        /// The asset being wagered
+        asset: Asset,
         bet_amount: Amount,
         commit_hash: [u8; 32],
-        /// Game-specific parameters (serialized)
-        game_params: String,
+        /// Tip of the house seed chain the bet is placed against (must be the active one)
+        house_chain_tip: [u8; 32],
+        /// The game and its parameters
+        game_params: GameParams,
     },
-    /// Reveal the random value to determine game outcome
+    /// Reveal the player's random value
     Reveal {
         game_id: u64,
         reveal_value: [u8; 32],
     },
+    /// Install a new house seed hash chain once the current one runs out (house operator only)
+    InstallHouseChain { tip: [u8; 32], length: u64 },
+    /// Reveal the house seed bound to a game, the next link of its chain (house operator only)
+    RevealHouseSeed {
+        game_id: u64,
+        house_seed: [u8; 32],
+    },
+    /// Settle a game whose seeds weren't both revealed by its deadline (anyone). Without the
+    /// house seed the player is paid the bet's highest payout; a player who kept their seed
+    /// after the house revealed loses the stake. A Mines round or Blackjack hand the player left
+    /// idle past their deadline is cashed out or stood for them instead, and settles once the
+    /// house reveals its seed.
+    ClaimTimeout { game_id: u64 },
+    /// Set the client seed of the player's session (only before the first bet on a server seed chain)
+    SetClientSeed { client_seed: String },
+    /// Commit a hash chain of server seeds to a player's session by its tip (house operator only)
+    CommitServerSeed {
+        player: AccountOwner,
+        /// The chain tip, the only value of the chain published up front
+        server_seed_hash: [u8; 32],
+        length: u64,
+    },
+    /// Place a bet on the player's session seeds, or start a round of an interactive game such
+    /// as Mines. Settling takes a second transaction: the bet stays pending until the house
+    /// reveals the server seed it is bound to with `RevealServerSeed`, usually in the block
+    /// after the bet's. The seed can't be known to the contract when the bet is placed without
+    /// the player being able to choose bets that win on it.
+    PlaceSessionBet {
+        /// The asset being wagered
+        asset: Asset,
+        bet_amount: Amount,
+        /// The game and its parameters
+        game_params: GameParams,
+    },
+    /// Deal the round of an interactive session bet, committing to the root of its hidden
+    /// board or shoe (house operator only)
+    DealRound {
+        game_id: u64,
+        root: [u8; 32],
+        /// Positions shown as soon as the round is dealt
+        openings: Vec<Opening>,
+    },
+    /// Open the positions a round is waiting for against its root (house operator only)
+    AnswerRound { game_id: u64, openings: Vec<Opening> },
+    /// Reveal a cell of a Mines round once the house opens it; a mine or a cleared board ends
+    /// the round
+    RevealTile { game_id: u64, cell: u8 },
+    /// End a Mines round at the multiplier of the cells revealed so far
+    CashOut { game_id: u64 },
+    /// Take an action in a Blackjack hand; the dealer plays once every hand is done
+    PlayBlackjack { game_id: u64, action: BlackjackAction },
+    /// Set the rules new Blackjack hands are dealt with (house operator only)
+    SetBlackjackRules { rules: BlackjackRules },
+    /// Reveal the server seed a session bet is bound to, the next link of the session's chain,
+    /// settling the bet or the round it dealt once the round ended (house operator only)
+    RevealServerSeed {
+        game_id: u64,
+        server_seed: [u8; 32],
+    },
+    /// Switch the session to the next server seed chain once its games have settled
+    RotateServerSeed {
+        /// Optionally start the new chain with a new client seed
+        client_seed: Option<String>,
+    },
+    /// Open or update an asset's table, or close it with `None` (house operator only)
+    SetTableLimits {
+        asset: Asset,
+        limits: Option<TableLimits>,
+    },
+    /// Set the house edge of a game, in basis points, or restore its default with `None`
+    /// (house operator only). Bets keep the edge they were placed at.
+    SetHouseEdge {
+        game_type: GameType,
+        house_edge_bps: Option<u32>,
+    },
+    /// Open a crash round taking bets, committing to the hash of its seed (house operator only)
+    OpenCrashRound { seed_hash: [u8; 32] },
+    /// Bet on the open crash round, optionally cashing out automatically at a target
+    PlaceCrashBet {
+        round_id: u64,
+        asset: Asset,
+        bet_amount: Amount,
+        /// Multiplier (* 100) to cash out at automatically
+        auto_cash_out: Option<u32>,
+    },
+    /// Close the crash round to bets and start its curve (house operator only)
+    StartCrashRound { round_id: u64 },
+    /// Cash out the signer's bets on a running crash round at the curve's current multiplier
+    CashOutCrash { round_id: u64 },
+    /// Reveal the seed of a crash round once its curve has crashed, settling every bet on it
+    /// (house operator only)
+    EndCrashRound { round_id: u64, seed: [u8; 32] },
+    /// Settle a crash round the house left past its deadline (anyone). An open round refunds
+    /// its bets; a running one pays every bet where it left the curve, or the top of the curve
+    /// if it never cashed out.
+    ExpireCrashRound { round_id: u64 },
+    /// Add funds from the signer's account to the house bankroll (house operator only)
+    FundBankroll { asset: Asset, amount: Amount },
+    /// Take funds out of the house bankroll (house operator only)
+    WithdrawBankroll {
+        asset: Asset,
+        amount: Amount,
+        destination: Account,
+    },
 }
 
 /// Response from casino operations
@@ -49,12 +612,244 @@ pub enum CasinoResponse {
     WithdrawSuccess { new_balance: Amount },
     /// Game was placed successfully
     GamePlaced { game_id: u64 },
+    /// A new house seed chain is active
+    HouseChainInstalled { epoch: u32 },
+    /// One side revealed, waiting for the other seed before settling
+    RevealAccepted { game_id: u64 },
+    /// A game was settled without its seeds after its reveal deadline passed
+    GameTimedOut { game_id: u64, payout: Amount },
+    /// Session seeds were updated
+    SessionUpdated,
+    /// An interactive game's round was dealt and waits for the player
+    RoundDealt { game_id: u64 },
+    /// The round waits for the house to open a position or reveal its server seed
+    AwaitingHouse { game_id: u64 },
+    /// A Mines cell was opened; cashing out now pays `multiplier` (* 100), nothing after a mine
+    TileRevealed {
+        game_id: u64,
+        cell: u8,
+        multiplier: u32,
+    },
+    /// A crash round is taking bets
+    CrashRoundOpened { round_id: u64 },
+    /// A crash round's curve started
+    CrashRoundStarted { round_id: u64 },
+    /// The signer's bets on a crash round cashed out at `multiplier` (* 100), which pays if
+    /// the round hadn't crashed yet
+    CrashCashedOut { round_id: u64, multiplier: u32 },
+    /// A crash round crashed at `crash_point` (* 100) and its bets were settled
+    CrashRoundEnded { round_id: u64, crash_point: u32 },
+    /// A crash round passed its deadline and its bets were settled without the seed
+    CrashRoundExpired { round_id: u64 },
+    /// A Blackjack hand is waiting for the player's next action
+    BlackjackHandUpdated { game_id: u64 },
+    /// The Blackjack rules were updated
+    BlackjackRulesUpdated,
+    /// An asset's table limits were updated
+    TableUpdated,
+    /// A game's house edge was updated
+    HouseEdgeUpdated,
+    /// The house bankroll of an asset changed
+    BankrollUpdated { bankroll: Amount },
     /// Game completed with outcome
     GameCompleted {
         game_id: u64,
         outcome: String,
         payout: Amount,
     },
+    /// The operation was rejected without changing any balances or games
+    Error(CasinoError),
+}
+
+/// Why an operation was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CasinoError {
+    /// The operation must be signed by an account owner
+    Unauthenticated,
+    /// Only the house operator can perform the operation
+    NotHouseOperator,
+    /// The token is not accepted by this casino
+    UnsupportedAsset(Asset),
+    /// No table is open for the asset
+    TableClosed(Asset),
+    /// The minimum bet exceeds the maximum, or the exposure exceeds the whole bankroll
+    InvalidTableLimits,
+    /// The game pays a fixed table, or the edge is the whole bet
+    InvalidHouseEdge,
+    /// The bet is outside the table limits
+    BetOutsideLimits { min_bet: Amount, max_bet: Amount },
+    /// The player's balance doesn't cover the amount
+    InsufficientBalance { available: Amount, required: Amount },
+    /// The free house bankroll doesn't cover the amount
+    InsufficientBankroll { available: Amount, required: Amount },
+    /// The bet's worst case exceeds the table's share of the free bankroll
+    ExposureLimitExceeded { liability: Amount, max_exposure: Amount },
+    /// The game parameters describe a bet the game doesn't offer
+    InvalidGameParams(String),
+    /// The game is played over several operations and needs a hidden seed, so it can only be
+    /// placed as a session bet
+    SessionRequired,
+    /// The cell is not on the board
+    InvalidCell { cell: u8 },
+    /// The cell was already revealed
+    CellAlreadyRevealed { cell: u8 },
+    /// Cashing out needs at least one revealed cell
+    NoTilesRevealed,
+    /// The session bet isn't of a game dealt round by round
+    NoRoundToDeal,
+    /// The round waits for the house to open a position or reveal its server seed
+    AwaitingHouse,
+    /// The round waits for the player
+    AwaitingPlayer,
+    /// The openings aren't the positions the round waits for, or don't match its root
+    InvalidOpening,
+    /// The server seed deals another board or shoe than the round's root commits to
+    CommitmentMismatch,
+    /// The round's server seed is revealed once the round ends
+    RoundNotFinished,
+    /// No pending game has this ID
+    GameNotFound { game_id: u64 },
+    /// Only the player who placed the bet can reveal it
+    NotGameOwner,
+    /// The seed of this side was already revealed
+    AlreadyRevealed,
+    /// The revealed player seed doesn't match the commit hash
+    InvalidReveal,
+    /// No house seed chain has been installed
+    NoHouseChain,
+    /// The bet was placed against a chain tip that is no longer active
+    StaleHouseChain,
+    /// Every seed of the active house chain is assigned
+    HouseChainExhausted,
+    /// The active house chain still has unassigned seeds
+    HouseChainInUse,
+    /// A house seed chain needs at least one seed
+    EmptyHouseChain,
+    /// House seeds are revealed in chain order
+    HouseSeedOutOfOrder { expected_index: u64 },
+    /// The house seed doesn't hash to the previous link of the chain
+    InvalidHouseSeed,
+    /// The game's seeds can still be revealed until the deadline
+    RevealDeadlineNotReached { deadline: Timestamp },
+    /// Client seeds are 1 to 64 characters
+    InvalidClientSeed,
+    /// The session has no client seed
+    ClientSeedNotSet,
+    /// The client seed can only change before the first bet of a server seed chain
+    SessionInProgress,
+    /// The house has not committed a server seed chain to the session
+    NoServerSeed,
+    /// The session already has a server seed chain queued for the next rotation
+    NextServerSeedCommitted,
+    /// A game of the session is still in play or waiting for its server seed
+    GameInProgress,
+    /// Crash is only played in shared rounds, with `PlaceCrashBet`
+    CrashRoundRequired,
+    /// No crash round has this ID
+    CrashRoundNotFound { round_id: u64 },
+    /// The latest crash round hasn't ended yet
+    CrashRoundInProgress,
+    /// The crash round is not in the phase the operation needs
+    WrongCrashPhase { phase: CrashPhase },
+    /// The crash round takes no more bets
+    CrashRoundFull,
+    /// The signer has no bet on the crash round
+    NoCrashBet,
+    /// The signer's bets on the crash round have already cashed out
+    AlreadyCashedOut,
+    /// The curve hasn't reached the crash point yet, so the round can't end
+    CrashRoundRunning,
+    /// The revealed seed doesn't match the round's seed hash
+    InvalidRoundSeed,
+    /// The Blackjack table plays other rules than the bet asked for
+    BlackjackRulesMismatch { rules: BlackjackRules },
+    /// The Blackjack hand doesn't allow the action now
+    InvalidBlackjackAction(String),
+}
+
+impl std::fmt::Display for CasinoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CasinoError::Unauthenticated => write!(f, "Operation must be from an authenticated user"),
+            CasinoError::NotHouseOperator => write!(f, "Only the house operator can perform this operation"),
+            CasinoError::UnsupportedAsset(asset) => write!(f, "Asset {} is not accepted by this casino", asset),
+            CasinoError::TableClosed(asset) => write!(f, "No table open for asset {}", asset),
+            CasinoError::InvalidTableLimits => write!(f, "Invalid table limits"),
+            CasinoError::InvalidHouseEdge => write!(f, "Invalid house edge"),
+            CasinoError::BetOutsideLimits { min_bet, max_bet } => {
+                write!(f, "Bet must be between {} and {}", min_bet, max_bet)
+            }
+            CasinoError::InsufficientBalance { available, required } => {
+                write!(f, "Insufficient balance: {} available, {} required", available, required)
+            }
+            CasinoError::InsufficientBankroll { available, required } => {
+                write!(f, "Insufficient free house bankroll: {} available, {} required", available, required)
+            }
+            CasinoError::ExposureLimitExceeded { liability, max_exposure } => write!(
+                f,
+                "Potential payout risks {} of the house bankroll, at most {} allowed",
+                liability, max_exposure
+            ),
+            CasinoError::InvalidGameParams(reason) => write!(f, "Invalid game parameters: {}", reason),
+            CasinoError::SessionRequired => write!(f, "This game can only be played as a session bet"),
+            CasinoError::InvalidCell { cell } => write!(f, "Cell {} is not on the board", cell),
+            CasinoError::CellAlreadyRevealed { cell } => write!(f, "Cell {} is already revealed", cell),
+            CasinoError::NoTilesRevealed => write!(f, "Reveal a cell before cashing out"),
+            CasinoError::NoRoundToDeal => write!(f, "The bet isn't dealt round by round"),
+            CasinoError::AwaitingHouse => write!(f, "The round is waiting for the house"),
+            CasinoError::AwaitingPlayer => write!(f, "The round is waiting for the player"),
+            CasinoError::InvalidOpening => write!(f, "Invalid opening of the round's commitment"),
+            CasinoError::CommitmentMismatch => write!(f, "The server seed doesn't deal the committed round"),
+            CasinoError::RoundNotFinished => write!(f, "The round hasn't finished yet"),
+            CasinoError::GameNotFound { game_id } => write!(f, "Game {} not found", game_id),
+            CasinoError::NotGameOwner => write!(f, "Only the game owner can reveal"),
+            CasinoError::AlreadyRevealed => write!(f, "Seed already revealed"),
+            CasinoError::InvalidReveal => write!(f, "Invalid reveal value"),
+            CasinoError::NoHouseChain => write!(f, "No house seed chain installed"),
+            CasinoError::StaleHouseChain => write!(f, "Bet placed against a stale house chain"),
+            CasinoError::HouseChainExhausted => write!(f, "House seed chain exhausted"),
+            CasinoError::HouseChainInUse => write!(f, "Current house seed chain still has seeds left"),
+            CasinoError::EmptyHouseChain => write!(f, "House seed chain must not be empty"),
+            CasinoError::HouseSeedOutOfOrder { expected_index } => {
+                write!(f, "House seeds must be revealed in chain order, next index is {}", expected_index)
+            }
+            CasinoError::InvalidHouseSeed => write!(f, "Invalid house seed"),
+            CasinoError::RevealDeadlineNotReached { deadline } => {
+                write!(f, "Seeds can be revealed until {} (microseconds)", deadline.micros())
+            }
+            CasinoError::InvalidClientSeed => write!(f, "Client seed must be 1 to 64 characters"),
+            CasinoError::ClientSeedNotSet => write!(f, "Client seed not set"),
+            CasinoError::SessionInProgress => write!(f, "Rotate the server seed before changing the client seed"),
+            CasinoError::NoServerSeed => write!(f, "No server seed chain committed for session"),
+            CasinoError::NextServerSeedCommitted => write!(f, "Next server seed chain already committed"),
+            CasinoError::GameInProgress => write!(f, "A game of the session is still in play"),
+            CasinoError::CrashRoundRequired => write!(f, "Crash is only played by betting on a crash round"),
+            CasinoError::CrashRoundNotFound { round_id } => write!(f, "Crash round {} not found", round_id),
+            CasinoError::CrashRoundInProgress => write!(f, "The latest crash round hasn't ended yet"),
+            CasinoError::WrongCrashPhase { phase } => write!(f, "The crash round is {:?}", phase),
+            CasinoError::CrashRoundFull => write!(f, "The crash round takes no more bets"),
+            CasinoError::NoCrashBet => write!(f, "No bet on this crash round"),
+            CasinoError::AlreadyCashedOut => write!(f, "Bets on this crash round already cashed out"),
+            CasinoError::CrashRoundRunning => write!(f, "The crash round hasn't crashed yet"),
+            CasinoError::InvalidRoundSeed => write!(f, "Invalid crash round seed"),
+            CasinoError::BlackjackRulesMismatch { rules } => write!(
+                f,
+                "The table {} soft 17 and pays blackjack {:?}",
+                if rules.dealer_hits_soft_17 { "hits" } else { "stands on" },
+                rules.blackjack_payout
+            ),
+            CasinoError::InvalidBlackjackAction(reason) => write!(f, "Invalid blackjack action: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CasinoError {}
+
+/// The latest operation of an owner that was rejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rejection {
+    pub error: CasinoError,
+    pub timestamp: Timestamp,
 }
 
 impl ContractAbi for CasinoAbi {
@@ -71,22 +866,613 @@ impl ServiceAbi for CasinoAbi {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingGame {
     pub player: AccountOwner,
-    pub game_type: GameType,
+    pub asset: Asset,
     pub bet_amount: Amount,
     pub commit_hash: [u8; 32],
-    pub game_params: String,
+    /// House seed chain epoch the bet was placed against
+    pub house_epoch: u32,
+    /// Position of the bet's house seed in the chain (1-based)
+    pub house_index: u64,
+    pub game_params: GameParams,
     pub timestamp: Timestamp,
+    /// Worst-case loss of the house on this bet, locked in the bankroll until settlement
+    pub liability: Amount,
+    /// Player seed, once revealed
+    pub player_seed: Option<[u8; 32]>,
+    /// House seed, once revealed
+    pub house_seed: Option<[u8; 32]>,
+}
+
+/// How long the seeds of a bet can be revealed after it was placed. Past it, anyone can settle
+/// the bet with [`CasinoOperation::ClaimTimeout`].
+pub const REVEAL_TIMEOUT_MICROS: u64 = 60 * 60 * 1_000_000;
+
+/// When the seeds of a bet placed at `timestamp` are due
+pub fn reveal_deadline(timestamp: Timestamp) -> Timestamp {
+    Timestamp::from(timestamp.micros().saturating_add(REVEAL_TIMEOUT_MICROS))
 }
 
 /// A completed game outcome
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
 pub struct GameOutcome {
     pub game_id: u64,
     pub game_type: String,
+    /// The asset wagered and paid out
+    pub asset: String,
     pub bet_amount: String,
     pub payout_amount: String,
+    /// Parameters the bet was placed with (JSON-serialized `GameParams`)
+    pub game_params: String,
     pub outcome_details: String,
     pub timestamp: u64,
+    /// Revealed player seed (hex)
+    pub player_seed: String,
+    /// Revealed house seed (hex); for session bets, the server seed
+    pub house_seed: String,
+    /// House seed chain epoch
+    pub house_epoch: u32,
+    /// Position of the house seed in its chain (1-based)
+    pub house_index: u64,
+    /// Client seed, for session bets
+    pub client_seed: Option<String>,
+    /// Tip of the server seed chain (hex), for session bets
+    pub server_seed_hash: Option<String>,
+    /// Nonce of the bet within its session, for session bets
+    pub nonce: Option<u64>,
+    /// Crash round the bet was placed on, whose revealed seed is the house seed
+    #[serde(default)]
+    pub crash_round: Option<u64>,
+    /// Version of the sampling algorithm the outcome was computed with
+    pub algorithm_version: u8,
+    /// The outcome in structured form, for games that have one
+    #[serde(default)]
+    pub result: GameResult,
+}
+
+/// Structured outcome of a settled game, for clients to render exactly what was paid. Only
+/// the field of the game played is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct GameResult {
+    pub roulette: Option<RouletteResult>,
+    pub plinko: Option<PlinkoResult>,
+    pub wheel: Option<WheelResult>,
+    pub dice: Option<DiceResult>,
+    pub limbo: Option<LimboResult>,
+    pub crash: Option<CrashResult>,
+    pub blackjack: Option<BlackjackResult>,
+    pub baccarat: Option<BaccaratResult>,
+}
+
+/// Where a roulette ball landed, and what each chip on the layout paid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct RouletteResult {
+    /// Winning number, 0 to 36
+    pub number: u32,
+    /// Chips of the bet in the order they were placed; empty for a single bet
+    pub chips: Vec<RouletteChipResult>,
+}
+
+/// What a roulette chip paid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct RouletteChipResult {
+    /// The position, as outcome strings name it (e.g. `split:17-20`)
+    pub bet: String,
+    pub amount: Amount,
+    /// Multiplier (* 100) of the position
+    pub multiplier: u32,
+    pub payout: Amount,
+}
+
+/// Path of a plinko ball
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct PlinkoResult {
+    /// Bounce at each row from the top, 0 to the left and 1 to the right
+    pub path: Vec<u8>,
+    /// Slot the ball landed in, counted from the left
+    pub slot: u8,
+}
+
+/// A dice roll
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct DiceResult {
+    /// The roll in hundredths, 0 (0.00) to 9999 (99.99)
+    pub roll: u32,
+    pub won: bool,
+}
+
+/// Multiplier a limbo bet drew
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct LimboResult {
+    /// Drawn multiplier (* 100), 100 (1.00x) and up
+    pub multiplier: u32,
+    /// Whether it reached the target, paying the target multiplier
+    pub won: bool,
+}
+
+/// Where a crash round crashed, and where the bet left it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashResult {
+    /// Crash point of the round (* 100)
+    pub crash_point: u32,
+    /// Multiplier (* 100) the bet cashed out at, by hand or automatically, if it did
+    pub cashed_out_at: Option<u32>,
+    /// Whether it cashed out before the crash
+    pub won: bool,
+}
+
+/// Every card of a blackjack hand and what each hand returned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackjackResult {
+    /// The player's hands, more than one after splits
+    pub hands: Vec<BlackjackHandResult>,
+    /// Up card, hole card and draws
+    pub dealer: Vec<PlayingCard>,
+    pub dealer_total: u8,
+    /// Whether the player took insurance
+    pub insurance: bool,
+    /// The player's actions in order
+    pub actions: Vec<BlackjackAction>,
+}
+
+/// Every card of a baccarat coup, in the order each side drew them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct BaccaratResult {
+    /// Player's first two cards, then their third if drawn
+    pub player_cards: Vec<PlayingCard>,
+    /// Banker's first two cards, then their third if drawn
+    pub banker_cards: Vec<PlayingCard>,
+    pub player_total: u8,
+    pub banker_total: u8,
+    pub winner: BaccaratBet,
+    pub player_pair: bool,
+    pub banker_pair: bool,
+}
+
+/// One of the player's blackjack hands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackjackHandResult {
+    pub cards: Vec<PlayingCard>,
+    pub total: u8,
+    pub doubled: bool,
+    /// What the hand returned, in hundredths of the initial bet
+    pub multiplier: u32,
+}
+
+/// Segment a wheel stopped on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct WheelResult {
+    /// Index of the segment in the layout, clockwise from the top
+    pub segment: u32,
+    /// Multiplier (* 100) the segment pays
+    pub multiplier: u32,
+    pub color: WheelColor,
+}
+
+/// Accounting of one asset held in the application's custody (in attos).
+///
+/// Every atto in custody belongs to the house, to a player's balance, or to a pending bet, so
+/// `total_funds == house_equity + player_liabilities + locked_bets` after every operation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    /// Funds held in the application's custody
+    pub total_funds: u128,
+    /// House bankroll, including the part locked for pending bets
+    pub house_equity: u128,
+    /// Sum of all player balances
+    pub player_liabilities: u128,
+    /// Stakes of pending bets
+    pub locked_bets: u128,
+    /// Part of the house equity reserved for the worst case of pending bets
+    pub locked_liability: u128,
+}
+
+impl Ledger {
+    /// Whether every atto in custody is accounted for exactly once
+    pub fn is_balanced(&self) -> bool {
+        let accounted = self.house_equity
+            .checked_add(self.player_liabilities)
+            .and_then(|sum| sum.checked_add(self.locked_bets));
+        accounted == Some(self.total_funds) && self.locked_liability <= self.house_equity
+    }
+
+    /// House equity not reserved for pending bets
+    pub fn free_equity(&self) -> u128 {
+        self.house_equity.saturating_sub(self.locked_liability)
+    }
+
+    /// Funds received from a player and credited to their balance
+    pub fn deposit(&mut self, amount: u128) {
+        self.total_funds += amount;
+        self.player_liabilities += amount;
+    }
+
+    /// Funds paid out of a player's balance
+    pub fn withdraw(&mut self, amount: u128) {
+        assert!(self.player_liabilities >= amount, "Player liabilities underflow");
+        self.player_liabilities -= amount;
+        self.total_funds -= amount;
+    }
+
+    /// Funds added to the house bankroll
+    pub fn fund_house(&mut self, amount: u128) {
+        self.total_funds += amount;
+        self.house_equity += amount;
+    }
+
+    /// Funds taken out of the free house bankroll
+    pub fn withdraw_house(&mut self, amount: u128) {
+        assert!(self.free_equity() >= amount, "Insufficient free house bankroll");
+        self.house_equity -= amount;
+        self.total_funds -= amount;
+    }
+
+    /// Moves a stake from a player's balance into a pending bet and reserves its liability
+    pub fn lock_bet(&mut self, stake: u128, liability: u128) {
+        assert!(self.player_liabilities >= stake, "Player liabilities underflow");
+        assert!(self.free_equity() >= liability, "Insufficient free house bankroll");
+        self.player_liabilities -= stake;
+        self.locked_bets += stake;
+        self.locked_liability += liability;
+    }
+
+    /// Releases a pending bet: the house takes the stake and credits the payout to the player
+    pub fn settle_bet(&mut self, stake: u128, liability: u128, payout: u128) {
+        assert!(self.locked_bets >= stake && self.locked_liability >= liability, "Locked funds underflow");
+        self.locked_bets -= stake;
+        self.locked_liability -= liability;
+        let equity = self.house_equity + stake;
+        assert!(equity >= payout, "House bankroll cannot cover payout");
+        self.house_equity = equity - payout;
+        self.player_liabilities += payout;
+    }
+}
+
+/// A player's client seed + nonce session.
+///
+/// The house commits a hash chain of server seeds to the session by its tip, like a house
+/// seed chain. Bet `n` is bound to link `n + 1` and settles once the house reveals that link,
+/// so no server seed is known to the contract before the bet it decides.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerSession {
+    pub client_seed: String,
+    /// Server seed chain the next bet is bound to; the number of seeds assigned is the nonce
+    pub server_chain: Option<HouseSeedChain>,
+    /// Server seed chain the session switches to on rotation
+    pub next_server_chain: Option<HouseSeedChain>,
+    /// Bets waiting for their server seed
+    pub pending_bets: u32,
+    /// Interactive games still in play. Their server seed is revealed when they end, and seeds
+    /// are revealed in order, so no other bet is taken meanwhile.
+    pub open_rounds: u32,
+}
+
+impl PlayerSession {
+    /// Nonce of the next bet on the current chain
+    pub fn nonce(&self) -> u64 {
+        self.server_chain.as_ref().map_or(0, |chain| chain.assigned)
+    }
+}
+
+/// A session bet waiting for the house to reveal its server seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBet {
+    pub player: AccountOwner,
+    pub asset: Asset,
+    pub bet_amount: Amount,
+    pub game_params: GameParams,
+    pub timestamp: Timestamp,
+    /// Worst-case loss of the house on this bet, locked in the bankroll until settlement
+    pub liability: Amount,
+    pub client_seed: String,
+    /// Tip of the server seed chain the bet is bound to
+    pub server_seed_hash: [u8; 32],
+    /// The bet's server seed is link `nonce + 1` of the chain
+    pub nonce: u64,
+}
+
+/// A Mines round in play, dealt from a session bet.
+///
+/// The contract only holds the root of the house's commitment to the board: the house opens
+/// each cell the player reveals against it, and once the round ends reveals the server seed,
+/// which must deal a board with the same root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinesRound {
+    pub player: AccountOwner,
+    pub asset: Asset,
+    pub bet_amount: Amount,
+    pub timestamp: Timestamp,
+    /// Worst-case loss of the house on this round, locked in the bankroll until settlement
+    pub liability: Amount,
+    /// The mine count and the cells revealed so far
+    pub params: MinesParams,
+    /// Multiplier (* 100) cashing out pays now
+    pub multiplier: u32,
+    /// Root of the commitment to the board, with a leaf of 1 for each mine and 0 for each safe cell
+    pub board_root: [u8; 32],
+    /// Cell the player revealed that the house hasn't opened yet
+    pub pending_cell: Option<u8>,
+    /// Whether a mine, a cleared board or cashing out ended the round
+    pub finished: bool,
+    /// Since when the round waits for the house, which must answer by the reveal deadline
+    pub awaiting_house_since: Option<Timestamp>,
+    /// Block time of the last move on the round, by either side
+    pub last_action_at: Timestamp,
+    pub client_seed: String,
+    pub server_seed_hash: [u8; 32],
+    pub nonce: u64,
+}
+
+impl MinesRound {
+    /// When anyone can cash the round out for its player, while it waits for them
+    pub fn player_deadline(&self) -> Option<Timestamp> {
+        self.awaiting_house_since.is_none().then(|| reveal_deadline(self.last_action_at))
+    }
+
+    /// Cashes out the round of a player who missed their deadline, at its current multiplier
+    /// or the stake back before the first reveal. Like any cash-out, it then waits for the
+    /// house to reveal its server seed.
+    pub fn time_out_player(&mut self, now: Timestamp) -> Result<(), CasinoError> {
+        let deadline = self.player_deadline().ok_or(CasinoError::AwaitingHouse)?;
+        if now < deadline {
+            return Err(CasinoError::RevealDeadlineNotReached { deadline });
+        }
+        self.finished = true;
+        self.awaiting_house_since = Some(now);
+        self.last_action_at = now;
+        Ok(())
+    }
+}
+
+/// A position of a dealt round, opened against the root the house committed to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Opening {
+    pub position: u32,
+    pub value: u8,
+    /// Salt of the position's leaf, derived from the round's entropy
+    pub salt: [u8; 32],
+    /// Sibling hashes from the leaf up to the root
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Phase of a crash round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+pub enum CrashPhase {
+    /// Taking bets
+    Open,
+    /// The curve is rising and bets can cash out
+    Running,
+    /// The seed was revealed and every bet settled
+    Crashed,
+    /// The house missed the round's deadline and every bet was settled without the seed
+    Expired,
+}
+
+/// A shared crash round.
+///
+/// The house commits to the round's seed when opening it, so the crash point is fixed before
+/// any bet is taken. Cash-outs are timed by the block timestamp against `started_at`, and the
+/// seed is only accepted once the curve has passed the crash point it draws.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashRound {
+    pub phase: CrashPhase,
+    /// Hash of the round seed
+    pub seed_hash: [u8; 32],
+    /// House edge every bet of the round is placed at, in basis points
+    pub house_edge_bps: u32,
+    pub opened_at: Timestamp,
+    pub started_at: Option<Timestamp>,
+    pub crashed_at: Option<Timestamp>,
+    /// The round seed, once revealed
+    pub seed: Option<[u8; 32]>,
+    /// Crash point (* 100), once the seed is revealed
+    pub crash_point: Option<u32>,
+    /// Bets in the order they were placed
+    pub bets: Vec<CrashBet>,
+}
+
+/// A bet on a crash round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashBet {
+    pub game_id: u64,
+    pub player: AccountOwner,
+    pub asset: Asset,
+    pub bet_amount: Amount,
+    pub timestamp: Timestamp,
+    /// Worst-case loss of the house on this bet, locked in the bankroll until the round ends
+    pub liability: Amount,
+    /// The auto cash-out target and, once made, the manual cash-out
+    pub params: CrashParams,
+}
+
+impl CrashRound {
+    /// When anyone can expire the round with [`CasinoOperation::ExpireCrashRound`]: the reveal
+    /// timeout after it opened while it takes bets, or after its curve reached the top once
+    /// running. Ended rounds have none.
+    pub fn deadline(&self) -> Option<Timestamp> {
+        match self.phase {
+            CrashPhase::Open => Some(reveal_deadline(self.opened_at)),
+            CrashPhase::Running => {
+                let started_at = self.started_at.expect("Running crash rounds have started");
+                let topped_at = started_at.micros().saturating_add(games::crash::max_curve_micros());
+                Some(reveal_deadline(Timestamp::from(topped_at)))
+            }
+            CrashPhase::Crashed | CrashPhase::Expired => None,
+        }
+    }
+}
+
+/// A Blackjack hand in play, dealt from a session bet.
+///
+/// The contract only holds the root of the house's commitment to the shoe and the cards
+/// opened against it so far. The hole card stays hidden, and the dealer plays once the house
+/// reveals the server seed, which must shuffle a shoe with the same root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackjackRound {
+    pub player: AccountOwner,
+    pub asset: Asset,
+    /// Everything staked so far: the initial bet, doubles, splits and insurance
+    pub staked: Amount,
+    pub timestamp: Timestamp,
+    /// Worst-case loss of the house on this hand, locked in the bankroll until settlement
+    pub liability: Amount,
+    /// The rules, the initial bet and the actions so far
+    pub params: BlackjackParams,
+    /// Root of the commitment to the shoe and the dealer's peek
+    pub shoe_root: [u8; 32],
+    /// Values opened so far, by position
+    pub cards: BTreeMap<u32, u8>,
+    /// Since when the hand waits for the house, which must answer by the reveal deadline
+    pub awaiting_house_since: Option<Timestamp>,
+    /// Block time of the last move on the hand, by either side
+    pub last_action_at: Timestamp,
+    /// Whether the player missed their deadline, so every hand stands as its cards are opened
+    pub auto_stand: bool,
+    pub client_seed: String,
+    pub server_seed_hash: [u8; 32],
+    pub nonce: u64,
+}
+
+impl BlackjackRound {
+    /// When anyone can play out the hand for its player, while it waits for them
+    pub fn player_deadline(&self) -> Option<Timestamp> {
+        self.awaiting_house_since.is_none().then(|| reveal_deadline(self.last_action_at))
+    }
+
+    /// Plays out the hand of a player who missed their deadline: a pending insurance is
+    /// declined and every hand stands, including split hands once the house opens their cards.
+    /// The hand then waits for the house like any other.
+    pub fn time_out_player(&mut self, now: Timestamp) -> Result<(), CasinoError> {
+        let deadline = self.player_deadline().ok_or(CasinoError::AwaitingHouse)?;
+        if now < deadline {
+            return Err(CasinoError::RevealDeadlineNotReached { deadline });
+        }
+        self.auto_stand = true;
+        games::blackjack::stand_opened(&self.cards, &mut self.params).expect("Hands in play replay");
+        self.awaiting_house_since = Some(now);
+        self.last_action_at = now;
+        Ok(())
+    }
+}
+
+/// A published hash chain of house seeds.
+///
+/// The house picks a random final seed `s_N` and derives `s_(k-1) = SHA3(s_k)` down to the
+/// tip `s_0`, which is the only value published up front. Seeds are bound to bets from
+/// `s_1` upwards and revealed in the same order, so every reveal is checked against the
+/// previously revealed link and the whole epoch hashes back to the tip. Seeds of bets that
+/// timed out unrevealed are skipped rather than holding up the rest of the chain.
+///
+/// Session server seeds are committed the same way, one chain per session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HouseSeedChain {
+    pub epoch: u32,
+    /// The published commitment `s_0`
+    pub tip: [u8; 32],
+    /// Number of seeds in the chain
+    pub length: u64,
+    /// Number of seeds bound to bets so far
+    pub assigned: u64,
+    /// Number of seeds revealed so far
+    pub revealed: u64,
+    /// The most recently revealed link (the tip until the first reveal)
+    pub last_revealed: [u8; 32],
+    /// Indices after `revealed` whose bets timed out before their seed was revealed
+    #[serde(default)]
+    pub timed_out: Vec<u64>,
+}
+
+impl HouseSeedChain {
+    pub fn new(epoch: u32, tip: [u8; 32], length: u64) -> Self {
+        HouseSeedChain {
+            epoch,
+            tip,
+            length,
+            assigned: 0,
+            revealed: 0,
+            last_revealed: tip,
+            timed_out: Vec::new(),
+        }
+    }
+
+    /// Whether every seed of the chain has been bound to a bet
+    pub fn is_exhausted(&self) -> bool {
+        self.assigned >= self.length
+    }
+
+    /// Index of the next seed to reveal, past the seeds of bets that timed out
+    pub fn next_index(&self) -> u64 {
+        let mut index = self.revealed + 1;
+        while self.timed_out.contains(&index) {
+            index += 1;
+        }
+        index
+    }
+
+    /// Checks that `seed` is the link at `index` and records it as the last one revealed.
+    ///
+    /// Each seed discloses every link before it, so seeds are revealed in order. A seed after
+    /// timed-out ones hashes down to the last revealed link in as many steps as it skips.
+    pub fn reveal(&mut self, index: u64, seed: [u8; 32]) -> Result<(), CasinoError> {
+        let expected_index = self.next_index();
+        if index != expected_index {
+            return Err(CasinoError::HouseSeedOutOfOrder { expected_index });
+        }
+        let mut link = seed;
+        for _ in self.revealed..index {
+            link = hash_seed(&link);
+        }
+        if link != self.last_revealed {
+            return Err(CasinoError::InvalidHouseSeed);
+        }
+
+        self.timed_out.retain(|&skipped| skipped > index);
+        self.revealed = index;
+        self.last_revealed = seed;
+        Ok(())
+    }
+}
+
+/// Hash a seed into its public commitment, which for house seeds is the previous chain link
+pub fn hash_seed(seed: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(seed);
+    hasher.finalize().into()
+}
+
+/// Check revealed house seeds against the published tip of their chain.
+///
+/// `seeds` holds `(index, seed)` pairs sorted by index. Indices may skip positions (e.g. a
+/// bet whose player never revealed is missing from the history): a seed `k` positions after
+/// the previous one must hash down to it in exactly `k` steps. Returns the index of the first
+/// seed that does not link back.
+pub fn verify_house_chain(tip: &[u8; 32], seeds: &[(u64, [u8; 32])]) -> Result<(), u64> {
+    let mut previous = (0u64, *tip);
+    for (index, seed) in seeds {
+        if *index <= previous.0 {
+            return Err(*index);
+        }
+        let mut link = *seed;
+        for _ in previous.0..*index {
+            link = hash_seed(&link);
+        }
+        if link != previous.1 {
+            return Err(*index);
+        }
+        previous = (*index, *seed);
+    }
+    Ok(())
 }
 
 // ============================================
@@ -95,7 +1481,6 @@ pub struct GameOutcome {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sha3::{Digest, Sha3_256};
 
     #[test]
     fn test_game_type_serialization() {
@@ -114,6 +1499,11 @@ mod tests {
             GameType::Plinko,
             GameType::Mines,
             GameType::Wheel,
+            GameType::Dice,
+            GameType::Limbo,
+            GameType::Crash,
+            GameType::Blackjack,
+            GameType::Baccarat,
         ];
         
         for game in games {
@@ -281,6 +1671,998 @@ mod tests {
         assert!(result1 < segments, "Result should be within segments");
     }
 
+    #[test]
+    fn test_house_seed_chain() {
+        // Build a chain of 4 seeds from a random final seed
+        let mut seeds = vec![[9u8; 32]];
+        for _ in 0..3 {
+            let next = hash_seed(seeds.last().unwrap());
+            seeds.push(next);
+        }
+        seeds.reverse(); // seeds[0] = s_1 ... seeds[3] = s_4
+        let tip = hash_seed(&seeds[0]);
+
+        let indexed: Vec<(u64, [u8; 32])> = (1..).zip(seeds.iter().copied()).collect();
+        assert_eq!(verify_house_chain(&tip, &indexed), Ok(()));
+        // Gaps are bridged by hashing the later seed several times
+        assert_eq!(verify_house_chain(&tip, &[indexed[1], indexed[3]]), Ok(()));
+
+        let mut chain = HouseSeedChain::new(1, tip, 4);
+        assert_eq!(chain.reveal(2, seeds[1]), Err(CasinoError::HouseSeedOutOfOrder { expected_index: 1 }));
+        assert_eq!(chain.reveal(1, seeds[1]), Err(CasinoError::InvalidHouseSeed));
+        assert_eq!(chain.reveal(1, seeds[0]), Ok(()));
+        // A bet that timed out without its seed doesn't hold up the ones after it
+        chain.timed_out.push(2);
+        assert_eq!(chain.next_index(), 3);
+        assert_eq!(chain.reveal(3, seeds[2]), Ok(()));
+        assert!(chain.timed_out.is_empty());
+        assert_eq!((chain.revealed, chain.last_revealed), (3, seeds[2]));
+
+        let mut tampered = indexed.clone();
+        tampered[2].1 = [0u8; 32];
+        assert_eq!(verify_house_chain(&tip, &tampered), Err(3));
+    }
+
+    fn roulette(bet: RouletteBet) -> GameParams {
+        GameParams::Roulette(RouletteParams { bet: Some(bet), chips: Vec::new() })
+    }
+
+    fn chips(chips: Vec<(RouletteBet, u128)>) -> GameParams {
+        let chips = chips.into_iter()
+            .map(|(bet, tokens)| RouletteChip { bet, amount: Amount::from_tokens(tokens) })
+            .collect();
+        GameParams::Roulette(RouletteParams { bet: None, chips })
+    }
+
+    fn max_multiplier(params: &GameParams) -> u32 {
+        games::game_for(params).max_multiplier(params)
+    }
+
+    fn mines(grid_size: u8, mines: u8, picks: Vec<u8>) -> MinesParams {
+        MinesParams { grid_size, mines, house_edge_bps: 300, picks }
+    }
+
+    fn dice(target: u32, direction: DiceDirection) -> DiceParams {
+        DiceParams { target, direction, house_edge_bps: 100 }
+    }
+
+    fn limbo(target: u32) -> LimboParams {
+        LimboParams { target, house_edge_bps: 100 }
+    }
+
+    fn baccarat(bet: BaccaratBet) -> BaccaratParams {
+        BaccaratParams { bet, amount: None, player_pair: None, banker_pair: None }
+    }
+
+    #[test]
+    fn test_max_multiplier_bounds_payouts() {
+        let plinko = |rows| GameParams::Plinko(PlinkoParams { rows, risk: RiskLevel::Medium });
+        let wheel = GameParams::Wheel(WheelParams { segments: 20, risk: RiskLevel::Medium });
+        assert_eq!(max_multiplier(&roulette(RouletteBet::Straight(17))), 3600);
+        assert_eq!(max_multiplier(&roulette(RouletteBet::Color(RouletteColor::Red))), 200);
+        assert_eq!(max_multiplier(&plinko(16)), 11000);
+        assert_eq!(max_multiplier(&plinko(8)), 1300);
+        assert_eq!(max_multiplier(&GameParams::Plinko(PlinkoParams { rows: 16, risk: RiskLevel::High })), 100000);
+        assert_eq!(max_multiplier(&wheel), 300);
+        assert_eq!(max_multiplier(&GameParams::Wheel(WheelParams { segments: 50, risk: RiskLevel::High })), 4950);
+
+        let bets = [
+            roulette(RouletteBet::Straight(5)),
+            roulette(RouletteBet::HighLow(RouletteHalf::Low)),
+            plinko(8),
+            plinko(13),
+            GameParams::Mines(mines(5, 3, vec![0, 6, 12, 18])),
+            wheel,
+        ];
+        for params in bets {
+            let max = max_multiplier(&params);
+            for seed in 0..200u8 {
+                let multiplier = games::game_for(&params).calculate_outcome(games::entropy::ALGORITHM_VERSION, &[seed; 32], &params).unwrap().multiplier;
+                assert!(multiplier <= max, "{:?} paid {} over {}", params, multiplier, max);
+            }
+        }
+    }
+
+    #[test]
+    fn test_game_params_validation() {
+        let valid = [
+            roulette(RouletteBet::Straight(36)),
+            roulette(RouletteBet::OddEven(RouletteParity::Even)),
+            roulette(RouletteBet::Split(vec![20, 17])),
+            roulette(RouletteBet::Split(vec![0, 2])),
+            roulette(RouletteBet::Street(vec![34, 35, 36])),
+            roulette(RouletteBet::Street(vec![0, 2, 3])),
+            roulette(RouletteBet::Corner(vec![32, 33, 35, 36])),
+            roulette(RouletteBet::Corner(vec![0, 1, 2, 3])),
+            roulette(RouletteBet::SixLine(vec![31, 32, 33, 34, 35, 36])),
+            roulette(RouletteBet::Dozen(3)),
+            roulette(RouletteBet::Column(1)),
+            GameParams::Plinko(PlinkoParams { rows: 8, risk: RiskLevel::High }),
+            GameParams::Mines(mines(5, 24, Vec::new())),
+            GameParams::Mines(mines(3, 8, Vec::new())),
+            GameParams::Mines(mines(8, 63, Vec::new())),
+            GameParams::Wheel(WheelParams { segments: 10, risk: RiskLevel::Low }),
+            GameParams::Wheel(WheelParams { segments: 50, risk: RiskLevel::High }),
+            GameParams::Dice(dice(1, DiceDirection::Under)),
+            GameParams::Dice(dice(9_800, DiceDirection::Under)),
+            GameParams::Dice(dice(199, DiceDirection::Over)),
+            GameParams::Dice(dice(9_800, DiceDirection::Over)),
+            GameParams::Limbo(limbo(101)),
+            GameParams::Limbo(limbo(100_000_000)),
+            GameParams::Crash(CrashParams::default()),
+            GameParams::Crash(CrashParams { auto_cash_out: Some(100_000), ..CrashParams::default() }),
+        ];
+        for params in valid {
+            assert_eq!(games::game_for(&params).validate(&params), Ok(()), "{:?}", params);
+        }
+
+        let invalid = [
+            roulette(RouletteBet::Straight(37)),
+            // Not adjacent across the end of a row, nor diagonally
+            roulette(RouletteBet::Split(vec![3, 4])),
+            roulette(RouletteBet::Split(vec![1, 5])),
+            roulette(RouletteBet::Split(vec![7, 7])),
+            roulette(RouletteBet::Split(vec![1, 2, 3])),
+            roulette(RouletteBet::Street(vec![2, 3, 4])),
+            roulette(RouletteBet::Street(vec![0, 1, 3])),
+            roulette(RouletteBet::Corner(vec![3, 4, 6, 7])),
+            roulette(RouletteBet::Corner(vec![34, 35, 37, 38])),
+            roulette(RouletteBet::SixLine(vec![2, 3, 4, 5, 6, 7])),
+            roulette(RouletteBet::Dozen(0)),
+            roulette(RouletteBet::Column(4)),
+            GameParams::Plinko(PlinkoParams { rows: 7, risk: RiskLevel::Low }),
+            GameParams::Plinko(PlinkoParams { rows: 17, risk: RiskLevel::Medium }),
+            GameParams::Mines(mines(5, 0, Vec::new())),
+            GameParams::Mines(mines(5, 25, Vec::new())),
+            GameParams::Mines(mines(2, 1, Vec::new())),
+            GameParams::Mines(mines(9, 1, Vec::new())),
+            GameParams::Mines(MinesParams { house_edge_bps: BASIS_POINTS, ..mines(5, 3, Vec::new()) }),
+            // Cells are only revealed after the bet
+            GameParams::Mines(mines(5, 5, vec![3])),
+            // The 8-segment wheel is only replayed
+            GameParams::Wheel(WheelParams { segments: 8, risk: RiskLevel::Medium }),
+            GameParams::Wheel(WheelParams { segments: 15, risk: RiskLevel::Low }),
+            GameParams::Dice(dice(0, DiceDirection::Under)),
+            GameParams::Dice(dice(9_801, DiceDirection::Under)),
+            // Wins on 98.01% of the rolls
+            GameParams::Dice(dice(198, DiceDirection::Over)),
+            GameParams::Dice(DiceParams { house_edge_bps: BASIS_POINTS, ..dice(5_000, DiceDirection::Over) }),
+            GameParams::Limbo(limbo(100)),
+            GameParams::Limbo(limbo(100_000_001)),
+            GameParams::Limbo(LimboParams { house_edge_bps: BASIS_POINTS, ..limbo(200) }),
+            GameParams::Crash(CrashParams { auto_cash_out: Some(100), ..CrashParams::default() }),
+            GameParams::Crash(CrashParams { auto_cash_out: Some(100_001), ..CrashParams::default() }),
+            // Bets cash out by hand once the round runs
+            GameParams::Crash(CrashParams { cashed_out_at: Some(150), ..CrashParams::default() }),
+            // Blackjack actions are taken once the cards are dealt
+            GameParams::Blackjack(BlackjackParams { actions: vec![BlackjackAction::Hit], ..BlackjackParams::default() }),
+            // Side bets are staked alongside an amount on the main bet
+            GameParams::Baccarat(BaccaratParams { player_pair: Some(Amount::from_attos(10)), ..baccarat(BaccaratBet::Player) }),
+            GameParams::Baccarat(BaccaratParams {
+                amount: Some(Amount::from_attos(100)),
+                banker_pair: Some(Amount::ZERO),
+                ..baccarat(BaccaratBet::Banker)
+            }),
+        ];
+        for params in invalid {
+            assert!(matches!(games::game_for(&params).validate(&params), Err(CasinoError::InvalidGameParams(_))), "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn test_roulette_bets_pay_true_odds_but_zero() {
+        use games::{roulette::Roulette, Game};
+
+        let mut bets: Vec<RouletteBet> = (0..=36).map(RouletteBet::Straight).collect();
+        for first in 1..=36u8 {
+            bets.push(RouletteBet::Split(vec![first, first + 3]));
+            bets.push(RouletteBet::Split(vec![first, first + 1]));
+            bets.push(RouletteBet::Street(vec![first, first + 1, first + 2]));
+            bets.push(RouletteBet::Corner(vec![first, first + 1, first + 3, first + 4]));
+            bets.push(RouletteBet::SixLine((first..first + 6).collect()));
+        }
+        bets.extend([
+            RouletteBet::Split(vec![0, 1]),
+            RouletteBet::Split(vec![0, 2]),
+            RouletteBet::Split(vec![0, 3]),
+            RouletteBet::Street(vec![0, 1, 2]),
+            RouletteBet::Street(vec![0, 2, 3]),
+            RouletteBet::Corner(vec![0, 1, 2, 3]),
+        ]);
+        bets.extend((1..=3).flat_map(|third| [RouletteBet::Dozen(third), RouletteBet::Column(third)]));
+        bets.extend([
+            RouletteBet::Color(RouletteColor::Red),
+            RouletteBet::Color(RouletteColor::Black),
+            RouletteBet::OddEven(RouletteParity::Odd),
+            RouletteBet::OddEven(RouletteParity::Even),
+            RouletteBet::HighLow(RouletteHalf::Low),
+            RouletteBet::HighLow(RouletteHalf::High),
+        ]);
+
+        let mut legal = 0;
+        for bet in bets {
+            let params = roulette(bet);
+            if games::game_for(&params).validate(&params).is_err() {
+                continue;
+            }
+            legal += 1;
+            let GameParams::Roulette(roulette_params) = &params else { unreachable!() };
+            // Every legal bet returns 36 units over the 37 pockets
+            let returned: u32 = (0..37).map(|result| Roulette::multiplier(roulette_params, &result)).sum();
+            assert_eq!(returned, 3600, "{:?}", params);
+        }
+        // 37 straights, 57 + 3 splits, 12 + 2 streets, 22 + 1 corners, 11 six lines and 12 outside bets
+        assert_eq!(legal, 157);
+    }
+
+    #[test]
+    fn test_roulette_chips_share_one_spin() {
+        let params = chips(vec![
+            (RouletteBet::Split(vec![17, 20]), 1),
+            (RouletteBet::Dozen(2), 2),
+            (RouletteBet::Color(RouletteColor::Red), 1),
+        ]);
+        let game = games::game_for(&params);
+        assert_eq!(game.validate(&params), Ok(()));
+        assert_eq!(game.check_stake(&params, Amount::from_tokens(4)), Ok(()));
+        assert!(game.check_stake(&params, Amount::from_tokens(3)).is_err());
+        // 17 wins the split and the dozen: 18 + 6 on a stake of 4
+        assert_eq!(max_multiplier(&params), 600);
+
+        let stake = Amount::from_tokens(4);
+        let mut winning_spins = 0;
+        for seed in 0..100u8 {
+            let settled = game.calculate_outcome(games::entropy::ALGORITHM_VERSION, &[seed; 32], &params).unwrap();
+            let RouletteResult { number, chips } = settled.result.roulette.clone().unwrap();
+            assert_eq!(chips.len(), 3);
+            let paid = chips.iter().fold(Amount::ZERO, |total, chip| total.saturating_add(chip.payout));
+            assert_eq!(settled.payout(stake), paid);
+            assert!(paid.to_attos() <= stake.to_attos() * max_multiplier(&params) as u128 / 100);
+            let split = format!("Roulette: {}, Chips: split:17-20 {} pays ", number, Amount::from_tokens(1));
+            assert!(settled.details.starts_with(&split), "{}", settled.details);
+            if paid > Amount::ZERO {
+                winning_spins += 1;
+            }
+        }
+        assert!(winning_spins > 0);
+
+        // The exact payout isn't rounded through the multiplier
+        let thirds = GameParams::Roulette(RouletteParams {
+            bet: None,
+            chips: vec![
+                RouletteChip { bet: RouletteBet::Straight(0), amount: Amount::from_attos(1) },
+                RouletteChip { bet: RouletteBet::Straight(1), amount: Amount::from_attos(2) },
+            ],
+        });
+        let GameParams::Roulette(thirds_params) = &thirds else { unreachable!() };
+        use games::{roulette::Roulette, Game};
+        assert_eq!(Roulette::multiplier(thirds_params, &0), 1200);
+        assert_eq!(Roulette::payout(thirds_params, &0), Some(Amount::from_attos(36)));
+        assert_eq!(Roulette::max_multiplier(thirds_params), 2400);
+
+        let invalid = [
+            chips(Vec::new()),
+            chips(vec![(RouletteBet::Straight(5), 0)]),
+            chips(vec![(RouletteBet::Split(vec![1, 5]), 1)]),
+            chips((0..=games::roulette::MAX_CHIPS as u8).map(|number| (RouletteBet::Straight(number % 37), 1)).collect()),
+            GameParams::Roulette(RouletteParams {
+                bet: Some(RouletteBet::Straight(5)),
+                chips: vec![RouletteChip { bet: RouletteBet::Straight(6), amount: Amount::from_tokens(1) }],
+            }),
+        ];
+        for params in invalid {
+            assert!(games::game_for(&params).validate(&params).is_err(), "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn test_recorded_params() {
+        let params = roulette(RouletteBet::Color(RouletteColor::Black));
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(games::recorded_params(GameType::Roulette, &json), Some(params));
+        assert_eq!(games::recorded_params(GameType::Plinko, &json), None);
+
+        // Old string params settle exactly as before
+        let legacy = [
+            (GameType::Roulette, "number:17"),
+            (GameType::Roulette, "color:red"),
+            (GameType::Roulette, "high_low:high"),
+            (GameType::Plinko, "12"),
+            (GameType::Plinko, ""),
+            (GameType::Wheel, ""),
+        ];
+        for (game_type, game_params) in legacy {
+            let params = games::recorded_params(game_type, game_params).unwrap();
+            assert_eq!(params.game_type(), game_type);
+        }
+        let straight = roulette(RouletteBet::Straight(17));
+        let settled = games::game_for(&straight).calculate_outcome(2, &[3; 32], &straight).unwrap();
+        assert!(settled.details.ends_with("Bet: number:17"));
+        assert_eq!(
+            games::recorded_params(GameType::Plinko, "20"),
+            Some(GameParams::Plinko(PlinkoParams { rows: 16, risk: RiskLevel::Medium }))
+        );
+        // Spellings the outcome string wouldn't reproduce can't be verified
+        assert_eq!(games::recorded_params(GameType::Roulette, "straight:17"), None);
+        assert_eq!(games::recorded_params(GameType::Roulette, "color:green"), None);
+        // Mines used to pay a fixed multiplier without looking at the board
+        assert_eq!(games::recorded_params(GameType::Mines, "3:4"), None);
+    }
+
+    #[test]
+    fn test_mines_round_pays_revealed_cells() {
+        use games::{mines::{self, Mines}, Game};
+
+        let entropy = [11u8; 32];
+        let dealt = mines(5, 5, Vec::new());
+        let board = Mines::calculate_outcome(2, &entropy, &dealt).unwrap();
+        assert_eq!(board.len(), 5);
+        let safe: Vec<u8> = (0..25u8).filter(|cell| !board.contains(&(*cell as u32))).collect();
+
+        let cashed_out = mines(5, 5, safe[..3].to_vec());
+        assert_eq!(Mines::multiplier(&cashed_out, &board), mines::cash_out_multiplier(&dealt, 3));
+        assert!(mines::cash_out_multiplier(&dealt, 3) > mines::cash_out_multiplier(&dealt, 2));
+
+        let busted = mines(5, 5, vec![safe[0], board[0] as u8]);
+        assert_eq!(Mines::multiplier(&busted, &board), 0);
+
+        let cleared = mines(5, 5, safe);
+        assert_eq!(Mines::multiplier(&cleared, &board), Mines::max_multiplier(&dealt));
+
+        // Boards of every size deal their mines inside the grid
+        let big = mines(8, 10, Vec::new());
+        let board = Mines::calculate_outcome(2, &entropy, &big).unwrap();
+        assert_eq!(board.len(), 10);
+        assert!(board.iter().all(|&cell| cell < 64));
+    }
+
+    #[test]
+    fn test_idle_mines_round_times_out() {
+        use games::{mines::Mines, Game};
+
+        let dealt_at = Timestamp::from(1_000);
+        let mut round = MinesRound {
+            player: AccountOwner::CHAIN,
+            asset: Asset::Native,
+            bet_amount: Amount::from_tokens(1),
+            timestamp: dealt_at,
+            liability: Amount::from_tokens(10),
+            params: mines(5, 5, Vec::new()),
+            multiplier: 0,
+            board_root: [0; 32],
+            pending_cell: None,
+            finished: false,
+            awaiting_house_since: None,
+            last_action_at: dealt_at,
+            client_seed: "seed".to_string(),
+            server_seed_hash: [0; 32],
+            nonce: 0,
+        };
+
+        // The player has until the reveal timeout after the deal to act
+        let deadline = reveal_deadline(dealt_at);
+        assert_eq!(round.player_deadline(), Some(deadline));
+        let early = Timestamp::from(deadline.micros() - 1);
+        assert_eq!(round.time_out_player(early), Err(CasinoError::RevealDeadlineNotReached { deadline }));
+        assert!(!round.finished);
+
+        // Past it the round is cashed out and waits for the house, which can't be timed out again
+        round.time_out_player(deadline).unwrap();
+        assert!(round.finished);
+        assert_eq!(round.awaiting_house_since, Some(deadline));
+        assert_eq!(round.player_deadline(), None);
+        assert_eq!(round.time_out_player(deadline), Err(CasinoError::AwaitingHouse));
+
+        // Before the first reveal it pays the stake back, whatever the board
+        let board = Mines::calculate_outcome(games::entropy::ALGORITHM_VERSION, &[4; 32], &round.params).unwrap();
+        assert_eq!(Mines::multiplier(&round.params, &board), 100);
+    }
+
+    #[test]
+    fn test_mines_multipliers_exact() {
+        use games::{mines, MAX_MULTIPLIER};
+
+        // 25 / 20 fair odds less 3%, as the 5x5 board always paid
+        assert_eq!(mines::cash_out_multiplier(&mines(5, 5, Vec::new()), 1), 121);
+        assert_eq!(mines::cash_out_multiplier(&mines(5, 1, Vec::new()), 24), 2425);
+        // C(25, 12) / C(13, 12) = 5_200_300 / 13 fair
+        assert_eq!(mines::cash_out_multiplier(&mines(5, 12, Vec::new()), 12), 38_802_238);
+        // Without an edge, one mine in nine cells pays exactly 9x on a cleared board
+        let fair = MinesParams { house_edge_bps: 0, ..mines(3, 1, Vec::new()) };
+        assert_eq!(mines::cash_out_multiplier(&fair, 8), 900);
+        // The first pick never pays less than the stake back
+        assert_eq!(mines::cash_out_multiplier(&mines(8, 1, Vec::new()), 1), 100);
+        // Huge boards are capped instead of overflowing
+        assert_eq!(mines::cash_out_multiplier(&mines(8, 32, Vec::new()), 32), MAX_MULTIPLIER);
+
+        let table = mines::multiplier_table(&mines(4, 3, Vec::new()));
+        assert_eq!(table.len(), 13);
+        assert!(table.windows(2).all(|pair| pair[0] <= pair[1]));
+        let higher_edge = MinesParams { house_edge_bps: 500, ..mines(4, 3, Vec::new()) };
+        assert!(mines::multiplier_table(&higher_edge)[5] < table[5]);
+    }
+
+    #[test]
+    fn test_dice_pays_inverse_win_chance() {
+        use games::{dice::Dice, Game};
+
+        // 50% to win less a 1% edge
+        assert_eq!(max_multiplier(&GameParams::Dice(dice(5_000, DiceDirection::Under))), 198);
+        assert_eq!(max_multiplier(&GameParams::Dice(dice(4_999, DiceDirection::Over))), 198);
+        // A single winning roll in 10,000
+        assert_eq!(max_multiplier(&GameParams::Dice(dice(1, DiceDirection::Under))), 990_000);
+        assert_eq!(max_multiplier(&GameParams::Dice(dice(9_800, DiceDirection::Under))), 101);
+        let fair = DiceParams { house_edge_bps: 0, ..dice(2_500, DiceDirection::Under) };
+        assert_eq!(max_multiplier(&GameParams::Dice(fair)), 400);
+
+        // Rolls on the target lose either way
+        let under = dice(5_000, DiceDirection::Under);
+        let over = dice(5_000, DiceDirection::Over);
+        assert_eq!(Dice::multiplier(&under, &4_999), 198);
+        assert_eq!(Dice::multiplier(&under, &5_000), 0);
+        assert_eq!(Dice::multiplier(&over, &5_000), 0);
+        assert_eq!(Dice::multiplier(&over, &5_001), 198);
+        assert_eq!(Dice::serialize_outcome(&under, &427), "Dice: Roll 4.27, Under 50.00");
+
+        // Every roll is drawn over the whole range
+        let params = GameParams::Dice(under);
+        let game = games::game_for(&params);
+        for seed in 0..200u8 {
+            let settled = game.calculate_outcome(games::entropy::ALGORITHM_VERSION, &[seed; 32], &params).unwrap();
+            let roll = settled.result.dice.unwrap();
+            assert!(roll.roll < 10_000);
+            assert_eq!(roll.won, roll.roll < 5_000);
+            assert_eq!(settled.multiplier, if roll.won { 198 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn test_limbo_reaches_target_at_inverse_odds() {
+        use games::{limbo::{self, Limbo}, Game, MAX_MULTIPLIER};
+
+        // Half the draws reach 1.98x at a 1% edge
+        assert_eq!(limbo::draw(1 << 31, 100), 198);
+        assert_eq!(limbo::draw((1 << 31) - 1, 100), 197);
+        assert_eq!(limbo::draw(3 << 30, 0), 400);
+        // Draws under 1.00x show as 1.00x, and the tail is capped
+        assert_eq!(limbo::draw(0, 100), 100);
+        assert_eq!(limbo::draw(u32::MAX, 100), MAX_MULTIPLIER);
+
+        let params = limbo(198);
+        assert_eq!(max_multiplier(&GameParams::Limbo(params.clone())), 198);
+        assert_eq!(Limbo::multiplier(&params, &197), 0);
+        assert_eq!(Limbo::multiplier(&params, &198), 198);
+        assert_eq!(Limbo::multiplier(&params, &MAX_MULTIPLIER), 198);
+        assert_eq!(Limbo::serialize_outcome(&params, &12_345), "Limbo: 123.45x, Target 1.98x");
+
+        let params = GameParams::Limbo(params);
+        let game = games::game_for(&params);
+        let mut won = 0;
+        for seed in 0..200u8 {
+            let settled = game.calculate_outcome(games::entropy::ALGORITHM_VERSION, &[seed; 32], &params).unwrap();
+            let draw = settled.result.limbo.unwrap();
+            assert!(draw.multiplier >= 100);
+            assert_eq!(draw.won, draw.multiplier >= 198);
+            assert_eq!(settled.multiplier, if draw.won { 198 } else { 0 });
+            won += draw.won as u32;
+        }
+        assert!((60..140).contains(&won), "{} of 200 draws reached 1.98x", won);
+    }
+
+    #[test]
+    fn test_crash_curve_and_cash_outs() {
+        use games::crash::{self, Crash, MAX_CRASH_MULTIPLIER};
+        use games::Game;
+
+        // 1.00x at the start, then 1% more every 100ms
+        assert_eq!(crash::curve_multiplier(0), 100);
+        assert_eq!(crash::curve_multiplier(99_999), 100);
+        assert_eq!(crash::curve_multiplier(100_000), 101);
+        assert_eq!(crash::curve_multiplier(7_000_000), 200);
+        assert_eq!(crash::curve_multiplier(u64::MAX), MAX_CRASH_MULTIPLIER);
+        let mut previous = 0;
+        for step in 0..800 {
+            let multiplier = crash::curve_multiplier(step * 100_000);
+            assert!(multiplier >= previous);
+            previous = multiplier;
+        }
+        // The round crashes once the curve passes the crash point, or stops at its top
+        assert!(crash::has_crashed(7_100_000, 200));
+        assert!(!crash::has_crashed(7_000_000, 200));
+        assert!(!crash::has_crashed(6_900_000, 200));
+        let top = crash::max_curve_micros();
+        assert_eq!(crash::curve_multiplier(top), MAX_CRASH_MULTIPLIER);
+        assert!(crash::curve_multiplier(top - 100_000) < MAX_CRASH_MULTIPLIER);
+        assert!(crash::has_crashed(top, MAX_CRASH_MULTIPLIER));
+        assert!(!crash::has_crashed(top - 100_000, MAX_CRASH_MULTIPLIER));
+
+        // Cashing out at the crash point still pays, and the earlier of both cash-outs counts
+        let manual = CrashParams { cashed_out_at: Some(150), ..CrashParams::default() };
+        assert_eq!(Crash::multiplier(&manual, &150), 150);
+        assert_eq!(Crash::multiplier(&manual, &149), 0);
+        let both = CrashParams { auto_cash_out: Some(120), cashed_out_at: Some(300), house_edge_bps: 100 };
+        assert_eq!(Crash::multiplier(&both, &500), 120);
+        assert_eq!(Crash::serialize_outcome(&both, &500), "Crash: 5.00x, Cashed out 1.20x");
+        let riding = CrashParams::default();
+        assert_eq!(Crash::multiplier(&riding, &MAX_CRASH_MULTIPLIER), 0);
+        // Expired rounds pay where bets left the curve, or its top
+        assert_eq!(crash::expired_multiplier(&both), 120);
+        assert_eq!(crash::expired_multiplier(&riding), MAX_CRASH_MULTIPLIER);
+        assert_eq!(crash::expired_multiplier(&riding), Crash::max_multiplier(&riding));
+        assert_eq!(crash::expired_details(&both, true), "Expired, Paid 1.20x");
+        assert_eq!(crash::expired_details(&both, false), "Expired, Refunded");
+        assert_eq!(Crash::serialize_outcome(&riding, &100), "Crash: 1.00x, No cash-out");
+        assert_eq!(max_multiplier(&GameParams::Crash(riding)), MAX_CRASH_MULTIPLIER);
+        assert_eq!(max_multiplier(&GameParams::Crash(both)), 120);
+
+        // Every bet on a round settles against the same crash point
+        let entropy = games::randomness::round_entropy(&[9; 32], 3);
+        let point = crash::crash_point(&entropy, 100);
+        for auto_cash_out in [101, 150, 1_000, 100_000] {
+            let params = GameParams::Crash(CrashParams { auto_cash_out: Some(auto_cash_out), cashed_out_at: None, house_edge_bps: 100 });
+            let settled = games::game_for(&params).calculate_outcome(games::entropy::ALGORITHM_VERSION, &entropy, &params).unwrap();
+            let result = settled.result.crash.unwrap();
+            assert_eq!(result.crash_point, point);
+            assert_eq!(result.won, auto_cash_out <= point);
+            assert_eq!(settled.multiplier, if result.won { auto_cash_out } else { 0 });
+        }
+    }
+
+    #[test]
+    fn test_blackjack_hands_and_settlement() {
+        use games::blackjack::{self, Blackjack};
+        use games::Game;
+        use BlackjackAction::*;
+
+        // Card indices run ace, 2 to 10, jack, queen, king in each suit
+        assert_eq!(blackjack::hand_total(&[0, 12]), (21, true));
+        assert_eq!(blackjack::hand_total(&[0, 0]), (12, true));
+        assert_eq!(blackjack::hand_total(&[0, 0, 8]), (21, true));
+        assert_eq!(blackjack::hand_total(&[9, 5, 13]), (17, false));
+        assert_eq!(blackjack::hand_total(&[9, 5, 7]), (24, false));
+        assert_eq!(PlayingCard::from_index(0).to_string(), "AC");
+        assert_eq!(PlayingCard::from_index(22).to_string(), "10D");
+        assert_eq!(PlayingCard::from_index(51).to_string(), "KS");
+
+        let shoe = blackjack::shoe(&[5; 32]);
+        assert_eq!(shoe.len(), 52 * blackjack::DECKS);
+        assert!((0..52).all(|card| shoe.iter().filter(|&&dealt| dealt == card).count() == blackjack::DECKS));
+
+        let params = |rules: BlackjackRules, actions: Vec<BlackjackAction>| BlackjackParams {
+            rules,
+            stake: Amount::from_attos(1_000),
+            actions,
+        };
+        // Stands the hands left in play, like settling a recorded hand does
+        let settle = |shoe: &[u8], params: &BlackjackParams| {
+            let mut standing = params.clone();
+            let mut table = blackjack::play(shoe, &standing).unwrap();
+            while !table.finished {
+                standing.actions.push(Stand);
+                table = blackjack::play(shoe, &standing).unwrap();
+            }
+            Blackjack::multiplier(params, &table)
+        };
+
+        // A natural pays 3:2 or 6:5 and ends the hand on the deal
+        let natural = [0, 9, 12, 7];
+        let six_to_five = BlackjackRules { blackjack_payout: BlackjackPayout::SixToFive, ..BlackjackRules::default() };
+        assert!(blackjack::play(&natural, &params(BlackjackRules::default(), vec![])).unwrap().finished);
+        assert_eq!(settle(&natural, &params(BlackjackRules::default(), vec![])), 250);
+        assert_eq!(settle(&natural, &params(six_to_five, vec![])), 220);
+        assert_eq!(max_multiplier(&GameParams::Blackjack(params(six_to_five, vec![]))), 220);
+        assert_eq!(
+            Blackjack::payout(&params(BlackjackRules::default(), vec![]), &blackjack::play(&natural, &params(BlackjackRules::default(), vec![])).unwrap()),
+            Some(Amount::from_attos(2_500))
+        );
+
+        // The dealer's soft 17 (6 up, ace in the hole) stands under S17 and draws a 4 under H17
+        let soft_17 = [9, 5, 8, 0, 3];
+        let hits_soft_17 = BlackjackRules { dealer_hits_soft_17: true, ..BlackjackRules::default() };
+        assert_eq!(settle(&soft_17, &params(BlackjackRules::default(), vec![Stand])), 200);
+        assert_eq!(settle(&soft_17, &params(hits_soft_17, vec![Stand])), 0);
+
+        // Split eights: the first draws a 3 and doubles into a 10 for 21, the second draws a
+        // king for 18 against the dealer's 19
+        let eights = [7, 9, 20, 8, 2, 9, 12];
+        let split = params(BlackjackRules::default(), vec![Split, Double, Stand]);
+        let table = blackjack::play(&eights, &split).unwrap();
+        assert!(table.finished);
+        assert_eq!((table.hand_multiplier(&split, 0), table.hand_multiplier(&split, 1)), (400, 0));
+        assert_eq!(blackjack::total_stake(&split), 300);
+        assert_eq!(
+            Blackjack::serialize_outcome(&split, &table),
+            "Blackjack: Dealer 10C 9C = 19; Hand 1 8C 3C 10C = 21 doubled 4.00x; Hand 2 8D KC = 18 0.00x"
+        );
+        // Hands wait for the player until every one of them is done
+        assert!(!blackjack::play(&eights, &params(BlackjackRules::default(), vec![Split, Double])).unwrap().finished);
+        assert!(blackjack::play(&eights, &params(BlackjackRules::default(), vec![Hit, Double])).is_err());
+        assert!(blackjack::play(&soft_17, &params(BlackjackRules::default(), vec![Split])).is_err());
+
+        // Insurance against an ace is decided first, and pays 2:1 on half the bet when the
+        // dealer has a blackjack
+        let ace_up = [9, 0, 8, 12];
+        assert!(blackjack::play(&ace_up, &params(BlackjackRules::default(), vec![Stand])).is_err());
+        assert!(blackjack::play(&soft_17, &params(BlackjackRules::default(), vec![Insurance])).is_err());
+        let insured = params(BlackjackRules::default(), vec![Insurance]);
+        assert!(blackjack::play(&ace_up, &insured).unwrap().finished);
+        assert_eq!(settle(&ace_up, &insured), 150);
+        assert_eq!(settle(&ace_up, &params(BlackjackRules::default(), vec![DeclineInsurance])), 0);
+        assert_eq!(blackjack::total_stake(&insured), 150);
+
+        // Settling a recorded hand replays its actions on the session's shoe
+        let entropy = [5; 32];
+        let recorded = GameParams::Blackjack(params(hits_soft_17, vec![]));
+        let settled = games::game_for(&recorded).calculate_outcome(games::entropy::ALGORITHM_VERSION, &entropy, &recorded).unwrap();
+        let result = settled.result.blackjack.as_ref().unwrap();
+        assert_eq!(result.hands.len(), 1);
+        assert_eq!(result.dealer[0], PlayingCard::from_index(shoe[1]));
+        assert_eq!(settled.payout(Amount::ZERO), Amount::from_attos(1_000 * settled.multiplier as u128 / 100));
+    }
+
+    #[test]
+    fn test_blackjack_played_from_opened_cards() {
+        use games::{blackjack, commitment};
+        use BlackjackAction::*;
+
+        let entropy = [5; 32];
+        let shoe = blackjack::shoe(&entropy);
+        let values = blackjack::committed_values(&shoe);
+        let root = commitment::root(&values, &entropy);
+        let mut params = BlackjackParams {
+            rules: BlackjackRules::default(),
+            stake: Amount::from_attos(1_000),
+            actions: vec![],
+        };
+
+        // The house opens each position the table waits for; the player declines insurance
+        // and hits to 17
+        let mut cards = BTreeMap::new();
+        let table = loop {
+            let table = blackjack::play_opened(&cards, &params).unwrap();
+            match table.waiting {
+                Some(position) if !table.player_done() => {
+                    let opening = commitment::open(&values, &entropy, position as u32);
+                    assert!(blackjack::is_valid_opening(opening.position, opening.value));
+                    assert!(commitment::verify(&root, &opening));
+                    cards.insert(opening.position, opening.value);
+                }
+                _ if table.player_done() => break table,
+                _ if table.insurance_offered() => params.actions.push(DeclineInsurance),
+                _ if blackjack::hand_total(&table.hands[table.active].cards).0 < 17 => params.actions.push(Hit),
+                _ => params.actions.push(Stand),
+            }
+        };
+
+        // The player saw their hands as dealt from the whole shoe, never the hole card
+        let full = blackjack::play(&shoe, &params).unwrap();
+        assert!(full.finished);
+        assert_eq!(table.hands, full.hands);
+        assert!(!cards.contains_key(&(blackjack::HOLE_CARD as u32)));
+        assert!(!blackjack::is_valid_opening(blackjack::HOLE_CARD as u32, shoe[blackjack::HOLE_CARD]));
+    }
+
+    #[test]
+    fn test_idle_blackjack_hand_stands() {
+        use games::{blackjack, commitment};
+        use BlackjackAction::*;
+
+        // Deals a hand from a shoe and leaves it waiting for the player after `actions`
+        fn dealt(entropy: [u8; 32], actions: Vec<BlackjackAction>) -> BlackjackRound {
+            let values = blackjack::committed_values(&blackjack::shoe(&entropy));
+            let mut round = BlackjackRound {
+                player: AccountOwner::CHAIN,
+                asset: Asset::Native,
+                staked: Amount::from_tokens(1),
+                timestamp: Timestamp::from(0),
+                liability: Amount::from_tokens(2),
+                params: BlackjackParams { rules: BlackjackRules::default(), stake: Amount::from_tokens(1), actions },
+                shoe_root: commitment::root(&values, &entropy),
+                cards: BTreeMap::new(),
+                awaiting_house_since: None,
+                last_action_at: Timestamp::from(1_000),
+                auto_stand: false,
+                client_seed: "seed".to_string(),
+                server_seed_hash: [0; 32],
+                nonce: 0,
+            };
+            answer(&mut round, &entropy);
+            round
+        }
+
+        // Opens every position the hand waits for, standing on for a timed-out player
+        fn answer(round: &mut BlackjackRound, entropy: &[u8; 32]) -> blackjack::Table {
+            let values = blackjack::committed_values(&blackjack::shoe(entropy));
+            loop {
+                let table = if round.auto_stand {
+                    blackjack::stand_opened(&round.cards, &mut round.params).unwrap()
+                } else {
+                    blackjack::play_opened(&round.cards, &round.params).unwrap()
+                };
+                match table.waiting {
+                    Some(position) if !table.player_done() => {
+                        round.cards.insert(position as u32, commitment::open(&values, entropy, position as u32).value);
+                    }
+                    _ => return table,
+                }
+            }
+        }
+
+        let seeds = || (0..=255u8).map(|seed| [seed; 32]);
+        let no_dealer_blackjack = |shoe: &[u8]| blackjack::committed_values(shoe)[blackjack::PEEK_POSITION] == 0;
+
+        // Against an ace the insurance offer is declined before the hand stands
+        let entropy = seeds().find(|entropy| blackjack::shoe(entropy)[1] % 13 == 0).unwrap();
+        let mut round = dealt(entropy, vec![]);
+        let deadline = reveal_deadline(round.last_action_at);
+        assert_eq!(round.player_deadline(), Some(deadline));
+        let early = Timestamp::from(deadline.micros() - 1);
+        assert_eq!(round.time_out_player(early), Err(CasinoError::RevealDeadlineNotReached { deadline }));
+        round.time_out_player(deadline).unwrap();
+        assert_eq!(round.awaiting_house_since, Some(deadline));
+        assert_eq!(round.player_deadline(), None);
+        assert_eq!(round.params.actions[0], DeclineInsurance);
+        assert!(answer(&mut round, &entropy).player_done());
+
+        // A split pair stands its first hand, then its second once the house opens its card
+        let entropy = seeds().find(|entropy| {
+            let shoe = blackjack::shoe(entropy);
+            shoe[0] % 13 == shoe[2] % 13 && shoe[0] % 13 != 0 && shoe[1] % 13 != 0 && no_dealer_blackjack(&shoe)
+        }).unwrap();
+        let mut round = dealt(entropy, vec![Split]);
+        round.time_out_player(deadline).unwrap();
+        let table = answer(&mut round, &entropy);
+        assert!(table.player_done());
+        assert_eq!(table.hands.len(), 2);
+        assert_eq!(round.params.actions, vec![Split, Stand, Stand]);
+
+        // The stands replay on the whole shoe once the house reveals it
+        let full = blackjack::play(&blackjack::shoe(&entropy), &round.params).unwrap();
+        assert!(full.finished);
+        let cards = |table: &blackjack::Table| table.hands.iter().map(|hand| hand.cards.clone()).collect::<Vec<_>>();
+        assert_eq!(cards(&full), cards(&table));
+    }
+
+    #[test]
+    fn test_baccarat_tableau_and_payouts() {
+        use games::baccarat::{self, Baccarat};
+        use games::Game;
+
+        // Card indices run ace, 2 to 10, jack, queen, king in each suit
+        assert_eq!(baccarat::hand_total(&[8, 12]), 9);
+        assert_eq!(baccarat::hand_total(&[4, 7]), 3);
+        assert_eq!(baccarat::hand_total(&[0, 13, 26]), 3);
+
+        // A natural stands both hands
+        let natural = baccarat::deal(&[8, 0, 12, 0, 3, 3]);
+        assert_eq!((natural.player.len(), natural.banker.len()), (2, 2));
+        assert_eq!(natural.winner(), BaccaratBet::Player);
+        // The player stands on 6, and the banker draws on 5 without a player third card
+        let standing = baccarat::deal(&[5, 2, 12, 1, 3]);
+        assert_eq!((standing.player.clone(), standing.banker.clone()), (vec![5, 12], vec![2, 1, 3]));
+        assert_eq!(standing.winner(), BaccaratBet::Banker);
+        // The banker stands on 3 against a player third card of 8, tying at 3
+        let tie = baccarat::deal(&[1, 2, 15, 9, 7, 0]);
+        assert_eq!((tie.player.len(), tie.banker.len()), (3, 2));
+        assert_eq!(tie.winner(), BaccaratBet::Tie);
+        // The banker draws on 6 against a player third card of 6 or 7, and stands against a 5
+        let drawing = baccarat::deal(&[1, 5, 2, 9, 5, 3]);
+        assert_eq!((drawing.banker.len(), drawing.winner()), (3, BaccaratBet::Player));
+        let holding = baccarat::deal(&[1, 5, 2, 9, 4, 3]);
+        assert_eq!((holding.banker.len(), holding.winner()), (2, BaccaratBet::Banker));
+
+        // Banker pays 0.95:1, tie 8:1, and bets on either side push on a tie
+        assert_eq!(Baccarat::multiplier(&baccarat(BaccaratBet::Banker), &standing), 195);
+        assert_eq!(Baccarat::multiplier(&baccarat(BaccaratBet::Player), &standing), 0);
+        assert_eq!(Baccarat::multiplier(&baccarat(BaccaratBet::Player), &tie), 100);
+        assert_eq!(Baccarat::multiplier(&baccarat(BaccaratBet::Tie), &tie), 900);
+        assert_eq!(max_multiplier(&GameParams::Baccarat(baccarat(BaccaratBet::Banker))), 195);
+        assert_eq!(
+            Baccarat::serialize_outcome(&baccarat(BaccaratBet::Banker), &standing),
+            "Baccarat: Player 6C KC = 6, Banker 3C 2C 4C = 9, Banker wins, Bet: banker"
+        );
+
+        // Pair side bets pay 11:1 on their own stakes, which add up to the bet amount
+        let pairs = baccarat::deal(&[0, 9, 13, 22, 3, 4]);
+        assert!(pairs.player_pair() && pairs.banker_pair());
+        assert_eq!(pairs.winner(), BaccaratBet::Player);
+        let with_sides = BaccaratParams {
+            amount: Some(Amount::from_attos(100)),
+            player_pair: Some(Amount::from_attos(10)),
+            banker_pair: Some(Amount::from_attos(10)),
+            ..baccarat(BaccaratBet::Player)
+        };
+        assert_eq!(Baccarat::payout(&with_sides, &pairs), Some(Amount::from_attos(440)));
+        assert_eq!(Baccarat::multiplier(&with_sides, &pairs), 366);
+        assert_eq!(max_multiplier(&GameParams::Baccarat(with_sides.clone())), 367);
+        let params = GameParams::Baccarat(with_sides);
+        assert!(games::game_for(&params).check_stake(&params, Amount::from_attos(120)).is_ok());
+        assert!(games::game_for(&params).check_stake(&params, Amount::from_attos(100)).is_err());
+
+        // Settling deals from the shoe shuffled from the entropy
+        let entropy = [7; 32];
+        let shoe = baccarat::shoe(&entropy);
+        assert_eq!(shoe.len(), 52 * baccarat::DECKS);
+        let coup = baccarat::deal(&shoe);
+        let settled = games::game_for(&params).calculate_outcome(games::entropy::ALGORITHM_VERSION, &entropy, &params).unwrap();
+        let result = settled.result.baccarat.unwrap();
+        assert_eq!(result.player_cards, coup.player.iter().map(|&card| PlayingCard::from_index(card)).collect::<Vec<_>>());
+        assert_eq!(result.banker_cards, coup.banker.iter().map(|&card| PlayingCard::from_index(card)).collect::<Vec<_>>());
+        assert_eq!(result.winner, coup.winner());
+    }
+
+    #[test]
+    fn test_plinko_tables_calibrated() {
+        use games::plinko::{self, MAX_ROWS, MIN_ROWS, TARGET_RTP_BPS};
+
+        for rows in MIN_ROWS..=MAX_ROWS {
+            let mut edges = Vec::new();
+            for risk in [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High] {
+                let table = plinko::multiplier_table(&PlinkoParams { rows, risk });
+                assert_eq!(table.len(), rows as usize + 1, "{} rows {:?}", rows, risk);
+                assert!(table.iter().eq(table.iter().rev()), "{} rows {:?} isn't symmetric", rows, risk);
+
+                // Sum of C(rows, slot) * multiplier over 2^rows drops, in basis points
+                let mut ways = 1u64;
+                let mut returned = 0u64;
+                for (slot, &multiplier) in table.iter().enumerate() {
+                    returned += ways * multiplier as u64;
+                    ways = ways * (rows as u64 - slot as u64) / (slot as u64 + 1);
+                }
+                let rtp_bps = returned * 100 / (1u64 << rows);
+                assert!(
+                    (TARGET_RTP_BPS as u64 - 10..=TARGET_RTP_BPS as u64).contains(&rtp_bps),
+                    "{} rows {:?} returns {} bps",
+                    rows,
+                    risk,
+                    rtp_bps
+                );
+                edges.push(table[0]);
+            }
+            assert!(edges.windows(2).all(|pair| pair[0] < pair[1]), "{} rows edges {:?}", rows, edges);
+        }
+    }
+
+    #[test]
+    fn test_plinko_fixed_drop() {
+        use games::plinko::Plinko;
+        use games::Game;
+
+        // The ball of game 7 played with player seed 0x01.. and house seed 0x02..
+        let params = PlinkoParams { rows: 12, risk: RiskLevel::High };
+        let entropy = games::randomness::combine_seeds(&[1; 32], &[2; 32], 7);
+        let drop = Plinko::calculate_outcome(games::entropy::ALGORITHM_VERSION, &entropy, &params).unwrap();
+        let bounces = [true, false, false, false, false, false, true, true, true, true, true, false];
+        assert_eq!(drop.bounces, bounces);
+        assert_eq!(drop.slot, 6);
+
+        // The result clients animate is the path the ball was paid on
+        let game_params = GameParams::Plinko(params.clone());
+        let settled = games::game_for(&game_params)
+            .calculate_outcome(games::entropy::ALGORITHM_VERSION, &entropy, &game_params)
+            .unwrap();
+        let PlinkoResult { path, slot } = settled.result.plinko.unwrap();
+        assert_eq!(path, bounces.map(u8::from));
+        assert_eq!(slot, 6);
+        assert_eq!(settled.multiplier, games::plinko::multiplier_table(&params)[6]);
+        assert_eq!(settled.multiplier, 20);
+        assert_eq!(
+            settled.details,
+            "Plinko: Slot 6, Path: RLLLLLRRRRRL, Multipliers: [17000, 2380, 810, 200, 70, 20, 20, 20, 70, 200, 810, 2380, 17000]"
+        );
+    }
+
+    #[test]
+    fn test_plinko_lands_on_right_bounces() {
+        let params = GameParams::Plinko(PlinkoParams { rows: 12, risk: RiskLevel::High });
+        let table = games::plinko::multiplier_table(&PlinkoParams { rows: 12, risk: RiskLevel::High });
+        let mut slots = std::collections::BTreeSet::new();
+        for seed in 0..50u8 {
+            let settled = games::game_for(&params).calculate_outcome(3, &[seed; 32], &params).unwrap();
+            let PlinkoResult { path, slot } = settled.result.plinko.unwrap();
+            assert_eq!(path.len(), 12);
+            assert!(path.iter().all(|&bounce| bounce <= 1));
+            assert_eq!(slot as usize, path.iter().filter(|&&bounce| bounce == 1).count());
+            assert_eq!(settled.multiplier, table[slot as usize]);
+
+            let letters: String = path.iter().map(|&bounce| if bounce == 1 { 'R' } else { 'L' }).collect();
+            assert_eq!(
+                settled.details,
+                format!("Plinko: Slot {}, Path: {}, Multipliers: {:?}", slot, letters, table)
+            );
+            slots.insert(slot);
+        }
+        assert!(slots.len() > 3, "{:?}", slots);
+
+        // Older versions still pay the single 17-slot table
+        let settled = games::game_for(&params).calculate_outcome(2, &[9; 32], &params).unwrap();
+        assert!(settled.details.starts_with("Plinko: Position "), "{}", settled.details);
+        assert!(settled.multiplier <= 1000);
+    }
+
+    #[test]
+    fn test_wheel_layouts_calibrated() {
+        use games::wheel::{self, SEGMENT_COUNTS, TARGET_RTP_BPS};
+
+        for segments in SEGMENT_COUNTS {
+            for risk in [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High] {
+                let layout = wheel::layout(&WheelParams { segments, risk });
+                assert_eq!(layout.len(), segments as usize);
+                // Multipliers are * 100, so their mean is the return in basis points
+                let returned: u32 = layout.iter().map(|segment| segment.multiplier).sum();
+                assert_eq!(returned / segments as u32, TARGET_RTP_BPS / 100, "{} segments {:?}", segments, risk);
+                assert_eq!(returned % segments as u32, 0);
+            }
+        }
+
+        let layout = wheel::layout(&WheelParams { segments: 30, risk: RiskLevel::Medium });
+        let params = GameParams::Wheel(WheelParams { segments: 30, risk: RiskLevel::Medium });
+        for seed in 0..50u8 {
+            let settled = games::game_for(&params).calculate_outcome(4, &[seed; 32], &params).unwrap();
+            let WheelResult { segment, multiplier, color } = settled.result.wheel.unwrap();
+            assert_eq!(layout[segment as usize], WheelSegment { multiplier, color });
+            assert_eq!(settled.multiplier, multiplier);
+            assert!(settled.details.starts_with(&format!("Wheel: Segment {} of 30, ", segment)), "{}", settled.details);
+        }
+
+        // Older versions still spin the 8-segment wheel, whatever the params
+        let settled = games::game_for(&params).calculate_outcome(3, &[9; 32], &params).unwrap();
+        assert!(settled.details.contains("Angle"), "{}", settled.details);
+        assert_eq!(settled.result, GameResult::default());
+    }
+
+    #[test]
+    fn test_ledger_stays_balanced() {
+        let mut ledger = Ledger::default();
+        ledger.fund_house(1_000);
+        ledger.deposit(300);
+        assert!(ledger.is_balanced());
+
+        // A winning bet: stake 100, worst case 360 paid, house risks 260
+        ledger.lock_bet(100, 260);
+        assert_eq!(ledger.free_equity(), 740);
+        assert!(ledger.is_balanced());
+        ledger.settle_bet(100, 260, 360);
+        assert_eq!(ledger.house_equity, 740);
+        assert_eq!(ledger.player_liabilities, 560);
+        assert!(ledger.is_balanced());
+
+        // A losing bet keeps the stake in the house
+        ledger.lock_bet(60, 60);
+        ledger.settle_bet(60, 60, 0);
+        assert_eq!(ledger.house_equity, 800);
+        assert_eq!((ledger.locked_bets, ledger.locked_liability), (0, 0));
+
+        ledger.withdraw(500);
+        ledger.withdraw_house(800);
+        assert_eq!(ledger, Ledger::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient free house bankroll")]
+    fn test_ledger_keeps_locked_equity() {
+        let mut ledger = Ledger::default();
+        ledger.fund_house(100);
+        ledger.deposit(10);
+        ledger.lock_bet(10, 80);
+        ledger.withdraw_house(30);
+    }
+
+    #[test]
+    fn test_casino_error_serialization() {
+        let error = CasinoError::InsufficientBalance {
+            available: Amount::from_attos(5),
+            required: Amount::from_attos(7),
+        };
+        let response = CasinoResponse::Error(error.clone());
+        let json = serde_json::to_string(&response).unwrap();
+        match serde_json::from_str::<CasinoResponse>(&json).unwrap() {
+            CasinoResponse::Error(back) => assert_eq!(back, error),
+            other => panic!("Unexpected response: {:?}", other),
+        }
+        assert!(error.to_string().starts_with("Insufficient balance"));
+    }
+
     #[test]
     fn test_different_seeds_different_results() {
         let reveal1: [u8; 32] = [1; 32];
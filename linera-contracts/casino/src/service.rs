@@ -7,10 +7,11 @@ mod state;
 
 use std::sync::Arc;
 
-use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
-use casino::{CasinoAbi, CasinoOperation, GameType};
+use async_graphql::{EmptySubscription, InputObject, Object, Request, Response, Schema, SimpleObject, Value};
+use casino::{games::{self, blackjack, mines, plinko, wheel}, Asset, BlackjackAction, BlackjackRules, CrashParams, CrashPhase, CrashRound, MinesParams, PlayingCard, PlinkoParams, RiskLevel, WheelParams, WheelSegment, CasinoAbi, CasinoError, CasinoOperation, CasinoParameters, GameParams, GameType, HouseSeedChain, Ledger, Opening, TableLimits, BASIS_POINTS};
 use linera_sdk::{
-    linera_base_types::{WithServiceAbi, Amount},
+    abis::fungible::FungibleTokenAbi,
+    linera_base_types::{WithServiceAbi, Account, AccountOwner, Amount, ApplicationId},
     views::View,
     Service, ServiceRuntime,
 };
@@ -29,7 +30,7 @@ impl WithServiceAbi for CasinoService {
 }
 
 impl Service for CasinoService {
-    type Parameters = ();
+    type Parameters = CasinoParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = CasinoState::load(runtime.root_view_storage_context())
@@ -45,6 +46,7 @@ impl Service for CasinoService {
         let schema = Schema::build(
             QueryRoot {
                 state: self.state.clone(),
+                runtime: self.runtime.clone(),
             },
             MutationRoot {
                 runtime: self.runtime.clone(),
@@ -58,6 +60,7 @@ impl Service for CasinoService {
 
 struct QueryRoot {
     state: Arc<CasinoState>,
+    runtime: Arc<ServiceRuntime<CasinoService>>,
 }
 
 #[Object]
@@ -67,24 +70,320 @@ impl QueryRoot {
         *self.state.next_game_id.get()
     }
 
-    /// Get total funds in the casino (in attos)
-    async fn total_funds(&self) -> String {
-        self.state.total_funds.get().to_string()
+    /// Get total funds of an asset in the casino (in attos)
+    async fn total_funds(&self, asset: String) -> async_graphql::Result<String> {
+        let asset: Asset = asset.parse()?;
+        Ok(self.ledger(asset).await?.total_funds.to_string())
+    }
+
+    /// Split the house bankroll of an asset into funds free to back new bets and liability
+    /// locked by pending ones
+    async fn bankroll(&self, asset: String) -> async_graphql::Result<BankrollStatus> {
+        let asset: Asset = asset.parse()?;
+        let ledger = self.ledger(asset).await?;
+        Ok(BankrollStatus {
+            total: ledger.house_equity.to_string(),
+            free: ledger.free_equity().to_string(),
+            locked: ledger.locked_liability.to_string(),
+        })
+    }
+
+    /// Break down where the funds of every asset in custody belong (in attos)
+    async fn ledger_summary(&self) -> async_graphql::Result<Vec<LedgerSummary>> {
+        let mut summaries = Vec::new();
+        for asset in self.state.ledgers.indices().await? {
+            let ledger = self.ledger(asset).await?;
+            summaries.push(LedgerSummary {
+                asset: asset.to_string(),
+                total_funds: ledger.total_funds.to_string(),
+                house_equity: ledger.house_equity.to_string(),
+                free_house_equity: ledger.free_equity().to_string(),
+                player_liabilities: ledger.player_liabilities.to_string(),
+                locked_bets: ledger.locked_bets.to_string(),
+                locked_liability: ledger.locked_liability.to_string(),
+                balanced: ledger.is_balanced(),
+            });
+        }
+        Ok(summaries)
+    }
+
+    /// Compare the recorded total funds of an asset with the application's actual on-chain balance
+    async fn custody_status(&self, asset: String) -> async_graphql::Result<CustodyStatus> {
+        let asset: Asset = asset.parse()?;
+        let custody: AccountOwner = self.runtime.application_id().forget_abi().into();
+        let recorded = self.ledger(asset).await?.total_funds;
+        let on_chain = match asset {
+            Asset::Native => self.runtime.owner_balance(custody),
+            Asset::Token(token) => token_balance(&self.runtime, token, custody)?,
+        }
+        .to_attos();
+        Ok(CustodyStatus {
+            recorded: recorded.to_string(),
+            on_chain: on_chain.to_string(),
+            matches: recorded == on_chain,
+            covered: on_chain >= recorded,
+        })
     }
 
-    /// Get a player's balance (in attos)
-    async fn player_balance(&self, owner: String) -> String {
+    /// Get every asset balance of a player (in attos)
+    async fn player_balance(&self, owner: String) -> Vec<AssetBalance> {
         // Parse owner from string
-        let owner: linera_sdk::linera_base_types::AccountOwner = match owner.parse() {
-            Ok(o) => o,
-            Err(_) => return "0".to_string(),
+        let Ok(owner) = owner.parse::<AccountOwner>() else {
+            return Vec::new();
+        };
+
+        let Ok(Some(balances)) = self.state.player_balances.try_load_entry(&owner).await else {
+            return Vec::new();
         };
+        let mut entries = Vec::new();
+        for asset in balances.indices().await.unwrap_or_default() {
+            let balance = balances.get(&asset).await.unwrap_or(None).unwrap_or(0);
+            entries.push(AssetBalance {
+                asset: asset.to_string(),
+                balance: balance.to_string(),
+            });
+        }
+        entries
+    }
+
+    /// Get the open tables with their limits and house bankroll
+    async fn tables(&self) -> async_graphql::Result<Vec<TableInfo>> {
+        let mut tables = Vec::new();
+        for asset in self.state.tables.indices().await? {
+            let Some(TableLimits { min_bet, max_bet, max_exposure_bps }) = self.state.tables.get(&asset).await? else {
+                continue;
+            };
+            let ledger = self.ledger(asset).await?;
+            tables.push(TableInfo {
+                asset: asset.to_string(),
+                min_bet: min_bet.to_attos().to_string(),
+                max_bet: max_bet.to_attos().to_string(),
+                max_exposure_bps,
+                bankroll: ledger.house_equity.to_string(),
+                free_bankroll: ledger.free_equity().to_string(),
+            });
+        }
+        Ok(tables)
+    }
+
+    /// Get the games bets can be placed on
+    async fn games(&self) -> Vec<String> {
+        games::registry().iter().map(|game| format!("{:?}", game.game_type())).collect()
+    }
+
+    /// Get the highest multiplier (* 100) a bet with these params can be paid at
+    async fn max_multiplier(&self, mut game_params: GameParams) -> async_graphql::Result<u32> {
+        let game = games::game_for(&game_params);
+        if let Some(house_edge_bps) = self.house_edge(game.game_type()).await? {
+            game.set_house_edge(&mut game_params, house_edge_bps);
+        }
+        game.validate(&game_params)?;
+        Ok(game.max_multiplier(&game_params))
+    }
+
+    /// Get the house edge of each game that has one, in basis points
+    async fn house_edges(&self) -> async_graphql::Result<Vec<HouseEdgeInfo>> {
+        let mut edges = Vec::new();
+        for game in games::registry() {
+            if let Some(house_edge_bps) = self.house_edge(game.game_type()).await? {
+                edges.push(HouseEdgeInfo {
+                    game_type: format!("{:?}", game.game_type()),
+                    house_edge_bps,
+                });
+            }
+        }
+        Ok(edges)
+    }
+
+    /// Get the multipliers (* 100) of every slot of a Plinko board, from the leftmost
+    async fn plinko_multipliers(&self, rows: u8, #[graphql(default)] risk: RiskLevel) -> async_graphql::Result<Vec<u32>> {
+        let params = PlinkoParams { rows, risk };
+        games::game(GameType::Plinko).validate(&GameParams::Plinko(params.clone()))?;
+        Ok(plinko::multiplier_table(&params).to_vec())
+    }
 
-        self.state.player_balances.get(&owner)
+    /// Get the segments of a wheel clockwise from the top, as results report them
+    async fn wheel_layout(&self, segments: u8, #[graphql(default)] risk: RiskLevel) -> async_graphql::Result<Vec<WheelSegment>> {
+        let params = WheelParams { segments, risk };
+        games::game(GameType::Wheel).validate(&GameParams::Wheel(params.clone()))?;
+        Ok(wheel::layout(&params))
+    }
+
+    /// Get the cash-out multipliers of a Mines board at the current house edge
+    async fn mines_multipliers(&self, grid_size: u8, mines: u8) -> async_graphql::Result<MinesTable> {
+        let params = MinesParams {
+            grid_size,
+            mines,
+            house_edge_bps: self.house_edge(GameType::Mines).await?.unwrap_or(mines::DEFAULT_HOUSE_EDGE_BPS),
+            picks: Vec::new(),
+        };
+        games::game(GameType::Mines).validate(&GameParams::Mines(params.clone()))?;
+        Ok(MinesTable {
+            grid_size,
+            mines,
+            house_edge_bps: params.house_edge_bps,
+            multipliers: mines::multiplier_table(&params),
+        })
+    }
+
+    /// Get a house seed chain by epoch, or the active one if no epoch is given
+    async fn house_chain(&self, epoch: Option<u32>) -> Option<HouseChainInfo> {
+        let epoch = epoch.unwrap_or(*self.state.house_epoch.get());
+        self.state.house_chains.get(&epoch)
+            .await
+            .unwrap_or(None)
+            .map(|chain| HouseChainInfo {
+                epoch: chain.epoch,
+                tip: hex::encode(chain.tip),
+                length: chain.length,
+                assigned: chain.assigned,
+                revealed: chain.revealed,
+            })
+    }
+
+    /// Check every settled game of an epoch against the epoch's published chain tip
+    async fn audit_house_epoch(&self, epoch: u32) -> Option<HouseEpochAudit> {
+        let chain = self.state.house_chains.get(&epoch).await.ok()??;
+        let history = self.state.game_history.read(..)
+            .await
+            .unwrap_or_default();
+
+        let mut seeds = Vec::new();
+        for game in history.iter().filter(|g| g.house_epoch == epoch) {
+            let Some(seed) = parse_hash(&game.house_seed) else {
+                return Some(HouseEpochAudit::invalid(&chain, seeds.len() as u64, game.house_index));
+            };
+            seeds.push((game.house_index, seed));
+        }
+        seeds.sort_by_key(|(index, _)| *index);
+
+        let settled_games = seeds.len() as u64;
+        Some(match casino::verify_house_chain(&chain.tip, &seeds) {
+            Ok(()) => HouseEpochAudit {
+                epoch,
+                tip: hex::encode(chain.tip),
+                settled_games,
+                valid: true,
+                first_invalid_index: None,
+            },
+            Err(index) => HouseEpochAudit::invalid(&chain, settled_games, index),
+        })
+    }
+
+    /// Get a player's session seeds (server seed chains by their tips only)
+    async fn session(&self, owner: String) -> Option<SessionInfo> {
+        let owner: AccountOwner = owner.parse().ok()?;
+        self.state.sessions.get(&owner)
             .await
-            .unwrap_or(Some(0))
-            .unwrap_or(0)
-            .to_string()
+            .unwrap_or(None)
+            .map(|session| SessionInfo {
+                nonce: session.nonce(),
+                client_seed: session.client_seed,
+                server_seed_hash: session.server_chain.as_ref().map(|chain| hex::encode(chain.tip)),
+                server_chain_length: session.server_chain.as_ref().map(|chain| chain.length),
+                next_server_seed_hash: session.next_server_chain.map(|chain| hex::encode(chain.tip)),
+                pending_bets: session.pending_bets,
+                open_rounds: session.open_rounds,
+            })
+    }
+
+    /// Get a Mines round in play; only the root of its board is known until it settles
+    async fn mines_round(&self, game_id: u64) -> async_graphql::Result<Option<MinesRoundInfo>> {
+        let Some(round) = self.state.mines_rounds.get(&game_id).await? else {
+            return Ok(None);
+        };
+        let revealed = round.params.picks.len() as u32;
+        Ok(Some(MinesRoundInfo {
+            game_id,
+            player: round.player,
+            asset: round.asset.to_string(),
+            bet_amount: round.bet_amount.to_string(),
+            player_deadline: round.player_deadline().map(|deadline| deadline.micros()),
+            next_multiplier: if revealed < mines::safe_cells(&round.params) {
+                mines::cash_out_multiplier(&round.params, revealed + 1)
+            } else {
+                round.multiplier
+            },
+            grid_size: round.params.grid_size,
+            mines: round.params.mines,
+            revealed: round.params.picks,
+            multiplier: round.multiplier,
+            board_root: hex::encode(round.board_root),
+            pending_cell: round.pending_cell,
+            finished: round.finished,
+        }))
+    }
+
+    /// Get a Blackjack hand in play from the cards the house opened; of the dealer's cards only
+    /// the up card shows until it settles
+    async fn blackjack_round(&self, game_id: u64) -> async_graphql::Result<Option<BlackjackRoundInfo>> {
+        let Some(round) = self.state.blackjack_rounds.get(&game_id).await? else {
+            return Ok(None);
+        };
+        let table = blackjack::play_opened(&round.cards, &round.params)?;
+        let awaiting_house = round.awaiting_house_since.is_some();
+        let hands = table.hands.iter()
+            .map(|hand| BlackjackHandInfo {
+                cards: hand.cards.iter().map(|&card| PlayingCard::from_index(card)).collect(),
+                total: blackjack::hand_total(&hand.cards).0,
+                doubled: hand.doubled,
+                done: hand.done,
+            })
+            .collect();
+        Ok(Some(BlackjackRoundInfo {
+            game_id,
+            player: round.player,
+            asset: round.asset.to_string(),
+            bet_amount: round.params.stake.to_string(),
+            staked: round.staked.to_string(),
+            player_deadline: round.player_deadline().map(|deadline| deadline.micros()),
+            rules: round.params.rules,
+            hands,
+            active_hand: table.active as u32,
+            dealer_up_card: PlayingCard::from_index(table.dealer[0]),
+            insurance_offered: table.insurance_offered(),
+            can_double: !awaiting_house && !table.insurance_offered() && table.can_double(),
+            can_split: !awaiting_house && !table.insurance_offered() && table.can_split(),
+            actions: round.params.actions,
+            shoe_root: hex::encode(round.shoe_root),
+            awaiting_house,
+        }))
+    }
+
+    /// Get the rules new Blackjack hands are dealt with
+    async fn blackjack_rules(&self) -> BlackjackRules {
+        *self.state.blackjack_rules.get()
+    }
+
+    /// Get a crash round by ID, or the latest one if no ID is given; its seed and crash point
+    /// stay hidden until it crashes
+    async fn crash_round(&self, round_id: Option<u64>) -> async_graphql::Result<Option<CrashRoundInfo>> {
+        let round_id = round_id.unwrap_or(*self.state.crash_round_id.get());
+        Ok(self.state.crash_rounds.get(&round_id).await?.map(|round| CrashRoundInfo::new(round_id, round)))
+    }
+
+    /// Get the latest crash rounds, newest first
+    async fn crash_rounds(&self, #[graphql(default = 20)] limit: u64) -> async_graphql::Result<Vec<CrashRoundInfo>> {
+        let latest = *self.state.crash_round_id.get();
+        let mut rounds = Vec::new();
+        for round_id in (1..=latest).rev().take(limit as usize) {
+            if let Some(round) = self.state.crash_rounds.get(&round_id).await? {
+                rounds.push(CrashRoundInfo::new(round_id, round));
+            }
+        }
+        Ok(rounds)
+    }
+
+    /// Get the latest operation of an owner the contract rejected, and why
+    async fn last_rejection(&self, owner: String) -> async_graphql::Result<Option<RejectionInfo>> {
+        let owner: AccountOwner = owner.parse()?;
+        Ok(self.state.last_rejections.get(&owner)
+            .await?
+            .map(|rejection| RejectionInfo {
+                code: error_code(&rejection.error),
+                message: rejection.error.to_string(),
+                timestamp: rejection.timestamp.micros(),
+            }))
     }
 
     /// Get the game history
@@ -103,92 +402,691 @@ impl QueryRoot {
     }
 }
 
+impl QueryRoot {
+    async fn ledger(&self, asset: Asset) -> async_graphql::Result<Ledger> {
+        Ok(self.state.ledgers.get(&asset).await?.unwrap_or_default())
+    }
+
+    /// House edge new bets on a game are placed at, if its payouts depend on one
+    async fn house_edge(&self, game_type: GameType) -> async_graphql::Result<Option<u32>> {
+        let Some(default_edge) = games::game(game_type).default_house_edge() else {
+            return Ok(None);
+        };
+        Ok(Some(self.state.house_edges.get(&game_type).await?.unwrap_or(default_edge)))
+    }
+}
+
 struct MutationRoot {
     runtime: Arc<ServiceRuntime<CasinoService>>,
 }
 
+/// Mutations only schedule operations. Malformed arguments are reported as GraphQL errors;
+/// operations the contract rejects are recorded and returned by the `lastRejection` query.
 #[Object]
 impl MutationRoot {
-    /// Deposit funds into the casino
-    async fn deposit(&self, amount: String) -> bool {
-        let amount = match amount.parse::<u128>() {
-            Ok(a) => Amount::from_attos(a),
-            Err(_) => return false,
-        };
+    /// Deposit funds of an asset into the casino
+    async fn deposit(&self, asset: String, amount: String) -> async_graphql::Result<bool> {
+        let asset: Asset = asset.parse()?;
+        let amount = parse_attos("amount", &amount)?;
 
-        let operation = CasinoOperation::Deposit { amount };
+        let operation = CasinoOperation::Deposit { asset, amount };
         self.runtime.schedule_operation(&operation);
-        true
+        Ok(true)
     }
 
-    /// Withdraw funds from the casino
-    async fn withdraw(&self, amount: String) -> bool {
-        let amount = match amount.parse::<u128>() {
-            Ok(a) => Amount::from_attos(a),
-            Err(_) => return false,
-        };
+    /// Withdraw funds of an asset from the casino to a destination account
+    async fn withdraw(&self, asset: String, amount: String, destination: Account) -> async_graphql::Result<bool> {
+        let asset: Asset = asset.parse()?;
+        let amount = parse_attos("amount", &amount)?;
 
-        let operation = CasinoOperation::Withdraw { amount };
+        let operation = CasinoOperation::Withdraw { asset, amount, destination };
         self.runtime.schedule_operation(&operation);
-        true
+        Ok(true)
     }
 
     /// Schedule a bet operation
     async fn place_bet(
         &self,
-        game_type: String,
+        asset: String,
         bet_amount: String,
         commit_hash: String,
-        game_params: String,
-    ) -> bool {
-        let game_type = match game_type.to_lowercase().as_str() {
-            "roulette" => GameType::Roulette,
-            "plinko" => GameType::Plinko,
-            "mines" => GameType::Mines,
-            "wheel" => GameType::Wheel,
-            _ => return false,
-        };
-
-        let bet_amount = match bet_amount.parse::<u128>() {
-            Ok(amount) => Amount::from_attos(amount),
-            Err(_) => return false,
-        };
-
-        let commit_hash: [u8; 32] = match hex::decode(&commit_hash) {
-            Ok(bytes) if bytes.len() == 32 => {
-                let mut arr = [0u8; 32];
-                arr.copy_from_slice(&bytes);
-                arr
-            }
-            _ => return false,
-        };
-
+        house_chain_tip: String,
+        game_params: GameParams,
+    ) -> async_graphql::Result<bool> {
+        let bet_amount = parse_attos("betAmount", &bet_amount)?;
+        let game = games::game_for(&game_params);
+        game.validate(&game_params)?;
+        game.check_stake(&game_params, bet_amount)?;
         let operation = CasinoOperation::PlaceBet {
-            game_type,
+            asset: asset.parse()?,
             bet_amount,
-            commit_hash,
+            commit_hash: required_hash("commitHash", &commit_hash)?,
+            house_chain_tip: required_hash("houseChainTip", &house_chain_tip)?,
             game_params,
         };
         self.runtime.schedule_operation(&operation);
-        true
+        Ok(true)
     }
 
     /// Schedule a reveal operation
-    async fn reveal(&self, game_id: u64, reveal_value: String) -> bool {
-        let reveal_value: [u8; 32] = match hex::decode(&reveal_value) {
-            Ok(bytes) if bytes.len() == 32 => {
-                let mut arr = [0u8; 32];
-                arr.copy_from_slice(&bytes);
-                arr
-            }
-            _ => return false,
+    async fn reveal(&self, game_id: u64, reveal_value: String) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::Reveal {
+            game_id,
+            reveal_value: required_hash("revealValue", &reveal_value)?,
         };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
 
-        let operation = CasinoOperation::Reveal {
+    /// Schedule installing a new house seed chain (house operator only)
+    async fn install_house_chain(&self, tip: String, length: u64) -> async_graphql::Result<bool> {
+        let tip = required_hash("tip", &tip)?;
+
+        let operation = CasinoOperation::InstallHouseChain { tip, length };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule revealing the house seed of a game (house operator only)
+    async fn reveal_house_seed(&self, game_id: u64, house_seed: String) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::RevealHouseSeed {
             game_id,
-            reveal_value,
+            house_seed: required_hash("houseSeed", &house_seed)?,
         };
         self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule settling a game whose seeds weren't revealed by its deadline
+    async fn claim_timeout(&self, game_id: u64) -> bool {
+        let operation = CasinoOperation::ClaimTimeout { game_id };
+        self.runtime.schedule_operation(&operation);
         true
     }
+
+    /// Schedule setting the client seed of the signer's session
+    async fn set_client_seed(&self, client_seed: String) -> bool {
+        let operation = CasinoOperation::SetClientSeed { client_seed };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule committing the tip of a server seed chain to a player's session (house operator only)
+    async fn commit_server_seed(&self, player: String, server_seed_hash: String, length: u64) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::CommitServerSeed {
+            player: player.parse::<AccountOwner>()?,
+            server_seed_hash: required_hash("serverSeedHash", &server_seed_hash)?,
+            length,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule revealing the server seed of a session bet (house operator only)
+    async fn reveal_server_seed(&self, game_id: u64, server_seed: String) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::RevealServerSeed {
+            game_id,
+            server_seed: required_hash("serverSeed", &server_seed)?,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule a bet on the signer's session seeds. It settles when the house reveals the
+    /// server seed it is bound to, in a later block, so poll `gameOutcome` for the result
+    async fn place_session_bet(
+        &self,
+        asset: String,
+        bet_amount: String,
+        game_params: GameParams,
+    ) -> async_graphql::Result<bool> {
+        let bet_amount = parse_attos("betAmount", &bet_amount)?;
+        let game = games::game_for(&game_params);
+        game.validate(&game_params)?;
+        game.check_stake(&game_params, bet_amount)?;
+        let operation = CasinoOperation::PlaceSessionBet {
+            asset: asset.parse()?,
+            bet_amount,
+            game_params,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule dealing the round of an interactive session bet by the root of its hidden
+    /// board (house operator only)
+    async fn deal_round(&self, game_id: u64, root: String, openings: Option<Vec<OpeningInput>>) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::DealRound {
+            game_id,
+            root: required_hash("root", &root)?,
+            openings: openings.unwrap_or_default()
+                .into_iter()
+                .map(OpeningInput::parse)
+                .collect::<async_graphql::Result<_>>()?,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule opening the positions a round waits for (house operator only)
+    async fn answer_round(&self, game_id: u64, openings: Vec<OpeningInput>) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::AnswerRound {
+            game_id,
+            openings: openings.into_iter()
+                .map(OpeningInput::parse)
+                .collect::<async_graphql::Result<_>>()?,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule revealing a cell of the signer's Mines round
+    async fn reveal_tile(&self, game_id: u64, cell: u8) -> bool {
+        let operation = CasinoOperation::RevealTile { game_id, cell };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule cashing out the signer's Mines round
+    async fn cash_out(&self, game_id: u64) -> bool {
+        let operation = CasinoOperation::CashOut { game_id };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule an action in the signer's Blackjack hand
+    async fn play_blackjack(&self, game_id: u64, action: BlackjackAction) -> bool {
+        let operation = CasinoOperation::PlayBlackjack { game_id, action };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule setting the rules new Blackjack hands are dealt with (house operator only)
+    async fn set_blackjack_rules(&self, rules: BlackjackRules) -> bool {
+        let operation = CasinoOperation::SetBlackjackRules { rules };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule opening a crash round committed to the hash of its seed (house operator only)
+    async fn open_crash_round(&self, seed_hash: String) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::OpenCrashRound {
+            seed_hash: required_hash("seedHash", &seed_hash)?,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule a bet on an open crash round
+    async fn place_crash_bet(
+        &self,
+        round_id: u64,
+        asset: String,
+        bet_amount: String,
+        auto_cash_out: Option<u32>,
+    ) -> async_graphql::Result<bool> {
+        let bet_amount = parse_attos("betAmount", &bet_amount)?;
+        let game_params = GameParams::Crash(CrashParams { auto_cash_out, ..CrashParams::default() });
+        games::game_for(&game_params).validate(&game_params)?;
+        let operation = CasinoOperation::PlaceCrashBet {
+            round_id,
+            asset: asset.parse()?,
+            bet_amount,
+            auto_cash_out,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule starting the curve of a crash round (house operator only)
+    async fn start_crash_round(&self, round_id: u64) -> bool {
+        let operation = CasinoOperation::StartCrashRound { round_id };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule cashing out the signer's bets on a running crash round
+    async fn cash_out_crash(&self, round_id: u64) -> bool {
+        let operation = CasinoOperation::CashOutCrash { round_id };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule revealing the seed of a crash round that crashed (house operator only)
+    async fn end_crash_round(&self, round_id: u64, seed: String) -> async_graphql::Result<bool> {
+        let operation = CasinoOperation::EndCrashRound {
+            round_id,
+            seed: required_hash("seed", &seed)?,
+        };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule settling a crash round the house left past its deadline
+    async fn expire_crash_round(&self, round_id: u64) -> bool {
+        let operation = CasinoOperation::ExpireCrashRound { round_id };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule switching the signer's session to its next server seed chain
+    async fn rotate_server_seed(&self, client_seed: Option<String>) -> bool {
+        let operation = CasinoOperation::RotateServerSeed { client_seed };
+        self.runtime.schedule_operation(&operation);
+        true
+    }
+
+    /// Schedule opening or updating an asset's table; omit every limit to close it (house operator only)
+    async fn set_table_limits(
+        &self,
+        asset: String,
+        min_bet: Option<String>,
+        max_bet: Option<String>,
+        max_exposure_bps: Option<u32>,
+    ) -> async_graphql::Result<bool> {
+        let asset: Asset = asset.parse()?;
+
+        let limits = match (min_bet, max_bet, max_exposure_bps) {
+            (None, None, None) => None,
+            (min_bet, max_bet, max_exposure_bps) => Some(TableLimits {
+                min_bet: match min_bet {
+                    Some(min_bet) => parse_attos("minBet", &min_bet)?,
+                    None => Amount::ZERO,
+                },
+                max_bet: match max_bet {
+                    Some(max_bet) => parse_attos("maxBet", &max_bet)?,
+                    None => Amount::MAX,
+                },
+                max_exposure_bps: max_exposure_bps.unwrap_or(BASIS_POINTS),
+            }),
+        };
+
+        let operation = CasinoOperation::SetTableLimits { asset, limits };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule setting a game's house edge; omit it to restore the default (house operator only)
+    async fn set_house_edge(&self, game_type: String, house_edge_bps: Option<u32>) -> async_graphql::Result<bool> {
+        let game_type: GameType = game_type.parse()?;
+        if games::game(game_type).default_house_edge().is_none() {
+            return Err(async_graphql::Error::new(format!("{game_type:?} has no configurable house edge")));
+        }
+
+        let operation = CasinoOperation::SetHouseEdge { game_type, house_edge_bps };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule adding funds from the signer's account to the house bankroll (house operator only)
+    async fn fund_bankroll(&self, asset: String, amount: String) -> async_graphql::Result<bool> {
+        let asset: Asset = asset.parse()?;
+        let amount = parse_attos("amount", &amount)?;
+
+        let operation = CasinoOperation::FundBankroll { asset, amount };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule taking funds out of the house bankroll (house operator only)
+    async fn withdraw_bankroll(&self, asset: String, amount: String, destination: Account) -> async_graphql::Result<bool> {
+        let asset: Asset = asset.parse()?;
+        let amount = parse_attos("amount", &amount)?;
+
+        let operation = CasinoOperation::WithdrawBankroll { asset, amount, destination };
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+}
+
+/// A rejected operation
+#[derive(SimpleObject)]
+struct RejectionInfo {
+    /// Name of the `CasinoError` variant, e.g. `InsufficientBalance`
+    code: String,
+    /// Human-readable reason
+    message: String,
+    /// Block time of the rejection (microseconds)
+    timestamp: u64,
+}
+
+/// Public view of a player's session; server seeds are only shown as hashes
+#[derive(SimpleObject)]
+struct SessionInfo {
+    client_seed: String,
+    /// Tip of the active server seed chain (hex)
+    server_seed_hash: Option<String>,
+    /// Seeds in the active server seed chain
+    server_chain_length: Option<u64>,
+    /// Tip of the server seed chain used after the next rotation (hex)
+    next_server_seed_hash: Option<String>,
+    /// Nonce of the next bet
+    nonce: u64,
+    /// Bets waiting for their server seed, which must settle before the chain can rotate
+    pending_bets: u32,
+    /// Interactive games in play, which must settle before the chain can rotate
+    open_rounds: u32,
+}
+
+/// Public view of a Mines round in play
+#[derive(SimpleObject)]
+struct MinesRoundInfo {
+    game_id: u64,
+    player: AccountOwner,
+    asset: String,
+    bet_amount: String,
+    /// Side of the board
+    grid_size: u8,
+    mines: u8,
+    /// Safe cells revealed so far, in order
+    revealed: Vec<u8>,
+    /// Multiplier (* 100) cashing out pays now
+    multiplier: u32,
+    /// Multiplier (* 100) after one more safe cell
+    next_multiplier: u32,
+    /// Root of the house's commitment to the board (hex)
+    board_root: String,
+    /// Cell waiting for the house to open it
+    pending_cell: Option<u8>,
+    /// Whether the round ended and waits for its server seed
+    finished: bool,
+    /// When anyone can cash the round out for the player (microseconds), while it waits for them
+    player_deadline: Option<u64>,
+}
+
+/// Public view of a Blackjack hand in play
+#[derive(SimpleObject)]
+struct BlackjackRoundInfo {
+    game_id: u64,
+    player: AccountOwner,
+    asset: String,
+    /// The initial bet
+    bet_amount: String,
+    /// Everything staked so far, with doubles, splits and insurance
+    staked: String,
+    rules: BlackjackRules,
+    hands: Vec<BlackjackHandInfo>,
+    /// Index of the hand the next action plays
+    active_hand: u32,
+    dealer_up_card: PlayingCard,
+    /// Whether the next action must take or decline insurance
+    insurance_offered: bool,
+    can_double: bool,
+    can_split: bool,
+    actions: Vec<BlackjackAction>,
+    /// Root of the house's commitment to the shoe (hex)
+    shoe_root: String,
+    /// Whether the hand waits for the house to open a card or reveal its server seed
+    awaiting_house: bool,
+    /// When anyone can stand the hand for the player (microseconds), while it waits for them
+    player_deadline: Option<u64>,
+}
+
+/// One of the player's hands in a Blackjack hand in play
+#[derive(SimpleObject)]
+struct BlackjackHandInfo {
+    cards: Vec<PlayingCard>,
+    total: u8,
+    doubled: bool,
+    /// Whether the player is done with the hand
+    done: bool,
+}
+
+/// Public view of a crash round
+#[derive(SimpleObject)]
+struct CrashRoundInfo {
+    round_id: u64,
+    phase: CrashPhase,
+    /// Hash of the round seed (hex), committed when the round opened
+    seed_hash: String,
+    /// The round seed (hex), once the round crashed
+    seed: Option<String>,
+    house_edge_bps: u32,
+    /// Block times of the phase changes (microseconds)
+    opened_at: u64,
+    started_at: Option<u64>,
+    crashed_at: Option<u64>,
+    /// Crash point (* 100), once the round crashed
+    crash_point: Option<u32>,
+    /// When anyone can expire the round (microseconds), until it ends
+    deadline: Option<u64>,
+    bets: Vec<CrashBetInfo>,
+}
+
+impl CrashRoundInfo {
+    fn new(round_id: u64, round: CrashRound) -> Self {
+        CrashRoundInfo {
+            round_id,
+            phase: round.phase,
+            seed_hash: hex::encode(round.seed_hash),
+            seed: round.seed.map(hex::encode),
+            house_edge_bps: round.house_edge_bps,
+            opened_at: round.opened_at.micros(),
+            started_at: round.started_at.map(|timestamp| timestamp.micros()),
+            crashed_at: round.crashed_at.map(|timestamp| timestamp.micros()),
+            deadline: round.deadline().map(|timestamp| timestamp.micros()),
+            crash_point: round.crash_point,
+            bets: round.bets.into_iter()
+                .map(|bet| CrashBetInfo {
+                    game_id: bet.game_id,
+                    player: bet.player,
+                    asset: bet.asset.to_string(),
+                    bet_amount: bet.bet_amount.to_string(),
+                    auto_cash_out: bet.params.auto_cash_out,
+                    cashed_out_at: bet.params.cashed_out_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A bet on a crash round; settled bets are in the game history under the same game ID
+#[derive(SimpleObject)]
+struct CrashBetInfo {
+    game_id: u64,
+    player: AccountOwner,
+    asset: String,
+    bet_amount: String,
+    /// Multiplier (* 100) the bet cashes out at automatically
+    auto_cash_out: Option<u32>,
+    /// Multiplier (* 100) of the curve when the player cashed out by hand
+    cashed_out_at: Option<u32>,
+}
+
+/// Cash-out multipliers of a Mines board
+#[derive(SimpleObject)]
+struct MinesTable {
+    grid_size: u8,
+    mines: u8,
+    house_edge_bps: u32,
+    /// Multipliers (* 100) after each safe cell, from the first to a cleared board
+    multipliers: Vec<u32>,
+}
+
+/// A game's house edge
+#[derive(SimpleObject)]
+struct HouseEdgeInfo {
+    game_type: String,
+    house_edge_bps: u32,
+}
+
+/// A player's balance of one asset (in attos)
+#[derive(SimpleObject)]
+struct AssetBalance {
+    /// `native` or the token application ID
+    asset: String,
+    balance: String,
+}
+
+/// An open table (amounts in attos)
+#[derive(SimpleObject)]
+struct TableInfo {
+    /// `native` or the token application ID
+    asset: String,
+    min_bet: String,
+    max_bet: String,
+    /// Largest share of the free bankroll a single bet may put at risk, in basis points
+    max_exposure_bps: u32,
+    /// Funds the house holds to pay winners
+    bankroll: String,
+    /// Part of the bankroll not locked by pending bets
+    free_bankroll: String,
+}
+
+/// Where the funds of an asset in custody belong (in attos)
+#[derive(SimpleObject)]
+struct LedgerSummary {
+    /// `native` or the token application ID
+    asset: String,
+    total_funds: String,
+    /// House bankroll, including locked liability
+    house_equity: String,
+    /// House bankroll available to back new bets
+    free_house_equity: String,
+    /// Sum of all player balances
+    player_liabilities: String,
+    /// Stakes of pending bets
+    locked_bets: String,
+    /// House bankroll reserved for the worst case of pending bets
+    locked_liability: String,
+    /// Total funds equal house equity plus player liabilities plus locked bets
+    balanced: bool,
+}
+
+/// House bankroll of an asset (in attos)
+#[derive(SimpleObject)]
+struct BankrollStatus {
+    total: String,
+    /// Available to back new bets and to withdraw
+    free: String,
+    /// Reserved for the worst case of pending bets
+    locked: String,
+}
+
+/// Recorded funds versus the application's on-chain balance (in attos)
+#[derive(SimpleObject)]
+struct CustodyStatus {
+    recorded: String,
+    on_chain: String,
+    /// The balance equals the recorded funds exactly
+    matches: bool,
+    /// The balance is at least the recorded funds (direct transfers may add a surplus)
+    covered: bool,
+}
+
+/// Public view of a house seed chain
+#[derive(SimpleObject)]
+struct HouseChainInfo {
+    epoch: u32,
+    /// Published chain tip (hex)
+    tip: String,
+    length: u64,
+    assigned: u64,
+    revealed: u64,
+}
+
+/// Result of checking an epoch's settled games against its chain tip
+#[derive(SimpleObject)]
+struct HouseEpochAudit {
+    epoch: u32,
+    /// Published chain tip (hex)
+    tip: String,
+    settled_games: u64,
+    valid: bool,
+    /// Chain index of the first house seed that does not link back to the tip
+    first_invalid_index: Option<u64>,
+}
+
+impl HouseEpochAudit {
+    fn invalid(chain: &HouseSeedChain, settled_games: u64, index: u64) -> Self {
+        HouseEpochAudit {
+            epoch: chain.epoch,
+            tip: hex::encode(chain.tip),
+            settled_games,
+            valid: false,
+            first_invalid_index: Some(index),
+        }
+    }
+}
+
+/// A position of a dealt round opened against its root, with hashes in hex
+#[derive(InputObject)]
+struct OpeningInput {
+    position: u32,
+    value: u8,
+    salt: String,
+    /// Sibling hashes from the leaf up to the root
+    proof: Vec<String>,
+}
+
+impl OpeningInput {
+    fn parse(self) -> async_graphql::Result<Opening> {
+        Ok(Opening {
+            position: self.position,
+            value: self.value,
+            salt: required_hash("salt", &self.salt)?,
+            proof: self.proof.iter()
+                .map(|hash| required_hash("proof", hash))
+                .collect::<async_graphql::Result<_>>()?,
+        })
+    }
+}
+
+/// Query an owner's balance from the fungible token application used as betting currency
+fn token_balance(
+    runtime: &ServiceRuntime<CasinoService>,
+    token: ApplicationId,
+    owner: AccountOwner,
+) -> async_graphql::Result<Amount> {
+    let request = Request::new(format!(
+        r#"query {{ accounts {{ entry(key: "{}") {{ value }} }} }}"#,
+        owner
+    ));
+    let response = runtime.query_application(token.with_abi::<FungibleTokenAbi>(), &request);
+    if let Some(error) = response.errors.first() {
+        return Err(error.message.clone().into());
+    }
+
+    let balance = ["accounts", "entry", "value"].iter().try_fold(&response.data, |value, key| match value {
+        Value::Object(fields) => fields.get(*key),
+        _ => None,
+    });
+    match balance {
+        None | Some(Value::Null) => Ok(Amount::ZERO),
+        Some(Value::String(value)) => Ok(value.parse()?),
+        Some(other) => Err(format!("Unexpected token balance: {}", other).into()),
+    }
+}
+
+/// Variant name of an error, as serialized
+fn error_code(error: &CasinoError) -> String {
+    match async_graphql::to_value(error) {
+        Ok(Value::String(code)) => code,
+        Ok(Value::Object(variant)) => variant.keys().next().map(|name| name.to_string()).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Parse an amount given in attos
+fn parse_attos(name: &str, value: &str) -> async_graphql::Result<Amount> {
+    value.parse::<u128>()
+        .map(Amount::from_attos)
+        .map_err(|_| format!("Invalid {}: expected an amount in attos", name).into())
+}
+
+/// Parse a hex-encoded 32-byte hash or seed argument
+fn required_hash(name: &str, value: &str) -> async_graphql::Result<[u8; 32]> {
+    parse_hash(value).ok_or_else(|| format!("Invalid {}: expected 32 bytes of hex", name).into())
+}
+
+/// Parse a hex-encoded 32-byte hash or seed
+fn parse_hash(value: &str) -> Option<[u8; 32]> {
+    match hex::decode(value) {
+        Ok(bytes) if bytes.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&bytes);
+            Some(arr)
+        }
+        _ => None,
+    }
 }
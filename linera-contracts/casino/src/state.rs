@@ -2,8 +2,8 @@
 // Casino state management
 
 use linera_sdk::linera_base_types::AccountOwner;
-use linera_sdk::views::{linera_views, RegisterView, MapView, LogView, RootView, ViewStorageContext};
-use casino::{PendingGame, GameOutcome};
+use linera_sdk::views::{linera_views, CollectionView, RegisterView, MapView, LogView, RootView, ViewStorageContext};
+use casino::{Asset, BlackjackRound, BlackjackRules, CrashRound, GameType, Ledger, MinesRound, PendingGame, GameOutcome, HouseSeedChain, PlayerSession, Rejection, SessionBet, TableLimits};
 
 /// The casino application state
 #[derive(RootView, async_graphql::SimpleObject)]
@@ -14,12 +14,49 @@ pub struct CasinoState {
     /// Pending games awaiting reveal
     #[graphql(skip)]
     pub pending_games: MapView<u64, PendingGame>,
+    /// Owner allowed to commit and reveal house seeds
+    #[graphql(skip)]
+    pub house_operator: RegisterView<Option<AccountOwner>>,
+    /// Epoch of the house seed chain new bets are bound to (0 before the first install)
+    pub house_epoch: RegisterView<u32>,
+    /// Every house seed chain installed so far, by epoch
+    #[graphql(skip)]
+    pub house_chains: MapView<u32, HouseSeedChain>,
     /// History of completed games
     pub game_history: LogView<GameOutcome>,
-    /// Total funds in the casino (in attos)
+    /// Client seed + nonce sessions by player
+    #[graphql(skip)]
+    pub sessions: MapView<AccountOwner, PlayerSession>,
+    /// Session bets waiting for their server seed, by game ID
+    #[graphql(skip)]
+    pub session_bets: MapView<u64, SessionBet>,
+    /// Mines rounds in play, by game ID
+    #[graphql(skip)]
+    pub mines_rounds: MapView<u64, MinesRound>,
+    /// Blackjack hands in play, by game ID
+    #[graphql(skip)]
+    pub blackjack_rounds: MapView<u64, BlackjackRound>,
+    /// Rules new Blackjack hands are dealt with
+    #[graphql(skip)]
+    pub blackjack_rules: RegisterView<BlackjackRules>,
+    /// Crash rounds by ID, kept once they end as the round history
+    #[graphql(skip)]
+    pub crash_rounds: MapView<u64, CrashRound>,
+    /// ID of the latest crash round (0 before the first)
+    pub crash_round_id: RegisterView<u64>,
+    /// Accounting of the funds held in the application's custody, per asset
+    #[graphql(skip)]
+    pub ledgers: MapView<Asset, Ledger>,
+    /// Betting limits of each asset with an open table
+    #[graphql(skip)]
+    pub tables: MapView<Asset, TableLimits>,
+    /// House edges set by the operator, in basis points, for games whose payouts depend on one
+    #[graphql(skip)]
+    pub house_edges: MapView<GameType, u32>,
+    /// Player balances (owner -> asset -> balance in attos)
     #[graphql(skip)]
-    pub total_funds: RegisterView<u64>,
-    /// Player balances (owner -> balance in attos)
+    pub player_balances: CollectionView<AccountOwner, MapView<Asset, u128>>,
+    /// The latest rejected operation of each signer
     #[graphql(skip)]
-    pub player_balances: MapView<AccountOwner, u128>,
+    pub last_rejections: MapView<AccountOwner, Rejection>,
 }
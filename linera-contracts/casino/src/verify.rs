@@ -0,0 +1,395 @@
+// Copyright (c) APT Casino. All rights reserved.
+// Offline verifier for settled casino games
+
+/*! Recomputes game outcomes with the same game code the contract settles with.
+
+Usage:
+    casino-verify game --game-type <type> --params <params> --game-id <id>
+                       (--player-seed <hex> --house-seed <hex> | --server-seed <hex> --client-seed <seed> --nonce <n>
+                        | --round-seed <hex> --round-id <n>)
+                       [--algorithm-version <v>] [--bet <amount>]
+    casino-verify history <game_history.json>
+
+`--params` takes the JSON of the bet's `GameParams` as recorded in its outcome (e.g.
+`{"Plinko":{"rows":12}}`), or the string format of bets placed before params were typed.
+
+History files are either a JSON array of game outcomes or the raw GraphQL response of a
+`gameHistory` query selecting every outcome field, with camelCase names and enums in capitals as
+the service renders them. Session bets are checked against the tip of their server seed chain.
+*/
+
+use std::{collections::HashMap, process::ExitCode};
+
+use casino::{
+    games::{self, entropy::ALGORITHM_VERSION, randomness},
+    verify_house_chain, GameOutcome, GameResult, GameType,
+};
+use linera_sdk::linera_base_types::Amount;
+use serde::de::DeserializeOwned;
+
+const USAGE: &str = "Usage:
+    casino-verify game --game-type <type> --params <params> --game-id <id>
+                       (--player-seed <hex> --house-seed <hex> | --server-seed <hex> --client-seed <seed> --nonce <n>
+                        | --round-seed <hex> --round-id <n>)
+                       [--algorithm-version <v>] [--bet <amount>]
+    casino-verify history <game_history.json>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("game") => verify_game(&args[1..]),
+        Some("history") => verify_history(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Recompute and print a single outcome
+fn verify_game(args: &[String]) -> Result<bool, String> {
+    let flags = parse_flags(args)?;
+
+    let game_type: GameType = required(&flags, "game-type")?.parse()?;
+    let game_params = flags.get("params").cloned().unwrap_or_default();
+    let game_params = games::recorded_params(game_type, &game_params)
+        .ok_or_else(|| format!("Invalid {:?} params: {}", game_type, game_params))?;
+    let algorithm_version = match flags.get("algorithm-version") {
+        Some(version) => version.parse().map_err(|_| format!("Invalid algorithm version: {}", version))?,
+        None => ALGORITHM_VERSION,
+    };
+
+    let entropy = if flags.contains_key("round-seed") {
+        let round_seed = parse_seed(required(&flags, "round-seed")?)?;
+        let round_id = parse_number(required(&flags, "round-id")?)?;
+        randomness::round_entropy(&round_seed, round_id)
+    } else if flags.contains_key("server-seed") {
+        let server_seed = parse_seed(required(&flags, "server-seed")?)?;
+        let client_seed = required(&flags, "client-seed")?;
+        let nonce = parse_number(required(&flags, "nonce")?)?;
+        randomness::session_entropy(&server_seed, client_seed, nonce)
+    } else {
+        let game_id = parse_number(required(&flags, "game-id")?)?;
+        let player_seed = parse_seed(required(&flags, "player-seed")?)?;
+        let house_seed = parse_seed(required(&flags, "house-seed")?)?;
+        randomness::combine_seeds(&player_seed, &house_seed, game_id)
+    };
+
+    let settled = games::game(game_type).calculate_outcome(algorithm_version, &entropy, &game_params)
+        .ok_or_else(|| format!("Unsupported algorithm version: {}", algorithm_version))?;
+    let multiplier = settled.multiplier;
+
+    println!("Outcome:    {}", settled.details);
+    if let Some(plinko) = &settled.result.plinko {
+        println!("Path:       {:?} (slot {})", plinko.path, plinko.slot);
+    }
+    if let Some(wheel) = &settled.result.wheel {
+        println!("Segment:    {} ({:?})", wheel.segment, wheel.color);
+    }
+    if let Some(crash) = &settled.result.crash {
+        println!("Crashed at: {}.{:02}x", crash.crash_point / 100, crash.crash_point % 100);
+    }
+    println!("Multiplier: {}.{:02}x", multiplier / 100, multiplier % 100);
+    if let Some(bet) = flags.get("bet") {
+        let bet: Amount = bet.parse().map_err(|_| format!("Invalid bet amount: {}", bet))?;
+        println!("Payout:     {}", settled.payout(bet));
+    }
+    Ok(true)
+}
+
+/// Check every entry of a game history export, reporting mismatches
+fn verify_history(args: &[String]) -> Result<bool, String> {
+    let [path] = args else {
+        return Err(USAGE.to_string());
+    };
+
+    let history: Vec<GameOutcome> = load_list(path, "gameHistory")?;
+
+    let (mut verified, mut mismatched, mut skipped) = (0, 0, 0);
+    for game in &history {
+        match check_outcome(game) {
+            Check::Verified => verified += 1,
+            Check::Mismatch(reason) => {
+                mismatched += 1;
+                println!("MISMATCH game {}: {}", game.game_id, reason);
+            }
+            Check::Skipped(reason) => {
+                skipped += 1;
+                println!("skipped  game {}: {}", game.game_id, reason);
+            }
+        }
+    }
+
+    println!(
+        "{} games: {} verified, {} mismatched, {} skipped",
+        history.len(),
+        verified,
+        mismatched,
+        skipped
+    );
+    Ok(mismatched == 0)
+}
+
+/// Result of checking one recorded outcome
+#[derive(Debug, PartialEq)]
+enum Check {
+    Verified,
+    Mismatch(String),
+    Skipped(String),
+}
+
+fn check_outcome(game: &GameOutcome) -> Check {
+    // Bets of a crash round that expired were paid without its seed ever being revealed
+    if game.crash_round.is_some() && game.house_seed.is_empty() {
+        return Check::Skipped("its crash round expired without a seed".to_string());
+    }
+    let entropy = match entropy_of(game) {
+        Ok(entropy) => entropy,
+        Err(reason) => return Check::Mismatch(reason),
+    };
+    let game_type = match game.game_type.parse::<GameType>() {
+        Ok(game_type) => game_type,
+        Err(reason) => return Check::Mismatch(reason),
+    };
+    let Some(game_params) = games::recorded_params(game_type, &game.game_params) else {
+        return Check::Skipped(format!("params \"{}\" can't be replayed", game.game_params));
+    };
+
+    let Some(settled) = games::game(game_type).calculate_outcome(game.algorithm_version, &entropy, &game_params) else {
+        return Check::Skipped(format!("unsupported algorithm version {}", game.algorithm_version));
+    };
+
+    if settled.details != game.outcome_details {
+        return Check::Mismatch(format!("outcome is \"{}\", recorded \"{}\"", settled.details, game.outcome_details));
+    }
+    // Outcomes recorded before results were structured, or exported without them, only have
+    // their details to check
+    if game.result != GameResult::default() && settled.result != game.result {
+        return Check::Mismatch(format!("result is {:?}, recorded {:?}", settled.result, game.result));
+    }
+    let Ok(bet) = game.bet_amount.parse::<Amount>() else {
+        return Check::Mismatch(format!("invalid bet amount {}", game.bet_amount));
+    };
+    let expected_payout = settled.payout(bet);
+    if game.payout_amount.parse::<Amount>() != Ok(expected_payout) {
+        return Check::Mismatch(format!("payout is {}, recorded {}", expected_payout, game.payout_amount));
+    }
+    Check::Verified
+}
+
+/// Rebuild the entropy a recorded game was settled with
+fn entropy_of(game: &GameOutcome) -> Result<[u8; 32], String> {
+    // Every bet on a crash round is settled from the round's seed, recorded as the house seed
+    if let Some(round_id) = game.crash_round {
+        return Ok(randomness::round_entropy(&parse_seed(&game.house_seed)?, round_id));
+    }
+    match (&game.client_seed, &game.server_seed_hash, game.nonce) {
+        (Some(client_seed), Some(server_seed_hash), Some(nonce)) => {
+            // Bet `nonce` is bound to link `nonce + 1` of the session's chain
+            let server_seed = parse_seed(&game.house_seed)?;
+            if verify_house_chain(&parse_seed(server_seed_hash)?, &[(nonce + 1, server_seed)]).is_err() {
+                return Err("server seed does not hash to its chain's tip".to_string());
+            }
+            Ok(randomness::session_entropy(&server_seed, client_seed, nonce))
+        }
+        _ => {
+            let player_seed = parse_seed(&game.player_seed)?;
+            let house_seed = parse_seed(&game.house_seed)?;
+            Ok(randomness::combine_seeds(&player_seed, &house_seed, game.game_id))
+        }
+    }
+}
+
+/// Load a JSON array, either bare or nested in a GraphQL response under `data.<field>`
+fn load_list<T: DeserializeOwned>(path: &str, field: &str) -> Result<Vec<T>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let mut value: serde_json::Value =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON in {}: {}", path, e))?;
+
+    if let Some(data) = value.get_mut("data") {
+        value = data.take();
+    }
+    if let Some(list) = value.get_mut(field) {
+        value = list.take();
+    }
+    serde_json::from_value(value).map_err(|e| format!("Unexpected format in {}: {}", path, e))
+}
+
+/// Parse `--flag value` pairs
+fn parse_flags(args: &[String]) -> Result<HashMap<String, String>, String> {
+    let mut flags = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let name = arg.strip_prefix("--").ok_or_else(|| format!("Unexpected argument: {}\n{}", arg, USAGE))?;
+        let value = args.next().ok_or_else(|| format!("Missing value for --{}", name))?;
+        flags.insert(name.to_string(), value.clone());
+    }
+    Ok(flags)
+}
+
+fn required<'a>(flags: &'a HashMap<String, String>, name: &str) -> Result<&'a String, String> {
+    flags.get(name).ok_or_else(|| format!("Missing --{}\n{}", name, USAGE))
+}
+
+fn parse_number(value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| format!("Invalid number: {}", value))
+}
+
+fn parse_seed(value: &str) -> Result<[u8; 32], String> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| format!("Invalid 32-byte hex seed: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+    use casino::{
+        Asset, BaccaratBet, BaccaratParams, BlackjackParams, BlackjackRules, CrashParams, GameParams, PlinkoParams, RiskLevel,
+        WheelParams,
+    };
+
+    /// Every field of a game outcome, as a `gameHistory` export for the verifier selects them
+    const HISTORY_QUERY: &str = "{ gameHistory {
+        gameId gameType asset betAmount payoutAmount gameParams outcomeDetails timestamp playerSeed houseSeed
+        houseEpoch houseIndex clientSeed serverSeedHash nonce crashRound algorithmVersion
+        result {
+            roulette { number chips { bet amount multiplier payout } }
+            plinko { path slot }
+            wheel { segment multiplier color }
+            dice { roll won }
+            limbo { multiplier won }
+            crash { crashPoint cashedOutAt won }
+            blackjack { hands { cards { rank suit } total doubled multiplier } dealer { rank suit } dealerTotal insurance actions }
+            baccarat { playerCards { rank suit } bankerCards { rank suit } playerTotal bankerTotal winner playerPair bankerPair }
+        }
+    } }";
+
+    struct History(Vec<GameOutcome>);
+
+    #[Object]
+    impl History {
+        async fn game_history(&self) -> &Vec<GameOutcome> {
+            &self.0
+        }
+    }
+
+    /// A game settled by commit-reveal, as the contract records it
+    fn recorded(game_id: u64, game_params: GameParams) -> GameOutcome {
+        let (player_seed, house_seed) = ([1; 32], [2; 32]);
+        let entropy = randomness::combine_seeds(&player_seed, &house_seed, game_id);
+        let settled = games::game_for(&game_params).calculate_outcome(ALGORITHM_VERSION, &entropy, &game_params).unwrap();
+        let bet = Amount::from_tokens(2);
+
+        GameOutcome {
+            game_id,
+            game_type: format!("{:?}", game_params.game_type()),
+            asset: Asset::Native.to_string(),
+            bet_amount: bet.to_string(),
+            payout_amount: settled.payout(bet).to_string(),
+            game_params: games::params_json(&game_params),
+            outcome_details: settled.details,
+            timestamp: 0,
+            player_seed: hex::encode(player_seed),
+            house_seed: hex::encode(house_seed),
+            house_epoch: 0,
+            house_index: 0,
+            client_seed: None,
+            server_seed_hash: None,
+            nonce: None,
+            crash_round: None,
+            algorithm_version: ALGORITHM_VERSION,
+            result: settled.result,
+        }
+    }
+
+    fn recorded_plinko() -> GameOutcome {
+        recorded(7, GameParams::Plinko(PlinkoParams { rows: 12, risk: RiskLevel::High }))
+    }
+
+    #[test]
+    fn test_check_outcome() {
+        let game = recorded_plinko();
+        assert_eq!(check_outcome(&game), Check::Verified);
+
+        let mut tampered = game.clone();
+        tampered.payout_amount = Amount::from_tokens(100).to_string();
+        assert!(matches!(check_outcome(&tampered), Check::Mismatch(reason) if reason.starts_with("payout is")));
+
+        // The fixed drop of lib's Plinko test, path and all
+        let plinko = game.result.plinko.clone().unwrap();
+        assert_eq!(plinko.path, [1, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 0]);
+        assert_eq!(plinko.slot, 6);
+        assert_eq!(game.payout_amount, Amount::from_attos(Amount::from_tokens(2).to_attos() / 5).to_string());
+
+        let mut tampered = game.clone();
+        tampered.result.plinko.as_mut().unwrap().path[0] = 0;
+        assert!(matches!(check_outcome(&tampered), Check::Mismatch(reason) if reason.starts_with("result is")));
+
+        let mut tampered = game.clone();
+        tampered.house_seed = hex::encode([3; 32]);
+        assert!(matches!(check_outcome(&tampered), Check::Mismatch(_)));
+
+        // Outcomes of algorithm versions the verifier doesn't know are skipped, not failed
+        let mut legacy = game.clone();
+        legacy.algorithm_version = 0;
+        assert_eq!(check_outcome(&legacy), Check::Skipped("unsupported algorithm version 0".to_string()));
+
+        // So are bets paid when their crash round expired, which have no seed to check
+        let mut expired = recorded(8, GameParams::Crash(CrashParams { auto_cash_out: Some(200), cashed_out_at: None, house_edge_bps: 100 }));
+        expired.house_seed = String::new();
+        expired.crash_round = Some(3);
+        assert!(matches!(check_outcome(&expired), Check::Skipped(_)));
+        // A seed left out of any other outcome is still a mismatch
+        let mut unseeded = game;
+        unseeded.house_seed = String::new();
+        assert!(matches!(check_outcome(&unseeded), Check::Mismatch(_)));
+    }
+
+    #[tokio::test]
+    async fn test_load_graphql_export() {
+        let history = vec![
+            recorded_plinko(),
+            recorded(8, GameParams::Wheel(WheelParams { segments: 20, risk: RiskLevel::High })),
+            recorded(9, GameParams::Crash(CrashParams { auto_cash_out: Some(200), cashed_out_at: Some(150), house_edge_bps: 100 })),
+            recorded(10, GameParams::Blackjack(BlackjackParams {
+                rules: BlackjackRules::default(),
+                stake: Amount::from_tokens(2),
+                actions: vec![],
+            })),
+            recorded(11, GameParams::Baccarat(BaccaratParams { bet: BaccaratBet::Banker, amount: None, player_pair: None, banker_pair: None })),
+        ];
+
+        // The response exactly as the service renders it, with camelCase fields and enums in
+        // capitals
+        let schema = Schema::new(History(history.clone()), EmptyMutation, EmptySubscription);
+        let response = schema.execute(HISTORY_QUERY).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let export = serde_json::json!({ "data": response.data.into_json().unwrap() });
+        let path = std::env::temp_dir().join(format!("casino-verify-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        // Both the GraphQL response and a bare array load
+        for contents in [export.to_string(), export["data"]["gameHistory"].to_string()] {
+            std::fs::write(path, contents).unwrap();
+            let loaded: Vec<GameOutcome> = load_list(path, "gameHistory").unwrap();
+            assert_eq!(loaded.len(), history.len());
+            for (game, recorded) in loaded.iter().zip(&history) {
+                assert_eq!(game.result, recorded.result);
+                assert_eq!(check_outcome(game), Check::Verified, "game {}", game.game_id);
+            }
+        }
+
+        std::fs::write(path, "{").unwrap();
+        assert!(load_list::<GameOutcome>(path, "gameHistory").unwrap_err().starts_with("Invalid JSON"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
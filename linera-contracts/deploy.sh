@@ -87,8 +87,11 @@ echo "📋 Step 5: Creating casino application..."
 # Initial funds for casino treasury (in attos: 1000 = 1000 LINERA initial funds)
 INITIAL_FUNDS="1000"
 
+# Fungible token application IDs accepted besides the native token
+TOKEN_PARAMETERS='{"tokens": []}'
+
 # Create application with initial funds
-APP_ID=$(linera create-application "$BYTECODE_ID" --json-argument "$INITIAL_FUNDS" 2>&1 | tee /dev/tty | grep -oE '[a-f0-9]{64}' | tail -1)
+APP_ID=$(linera create-application "$BYTECODE_ID" --json-parameters "$TOKEN_PARAMETERS" --json-argument "$INITIAL_FUNDS" 2>&1 | tee /dev/tty | grep -oE '[a-f0-9]{64}' | tail -1)
 
 if [ -z "$APP_ID" ]; then
     echo -e "${RED}❌ Failed to create application${NC}"
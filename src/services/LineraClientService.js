@@ -158,13 +158,47 @@ export async function getApplication(chain, applicationId) {
 export async function applicationQuery(app, graphqlString, options = null) {
   const raw = await app.query(graphqlString, options);
   // The SDK returns a JSON string; parse it to extract the data
+  let parsed;
   try {
-    const parsed = JSON.parse(raw);
-    // Linera wraps in { data: ... }, unwrap if present
-    return parsed.data !== undefined ? parsed.data : parsed;
+    parsed = JSON.parse(raw);
   } catch {
     return raw;
   }
+  // Malformed arguments are reported as GraphQL errors
+  if (parsed.errors?.length) {
+    throw new Error(parsed.errors.map((e) => e.message).join('; '));
+  }
+  // Linera wraps in { data: ... }, unwrap if present
+  return parsed.data !== undefined ? parsed.data : parsed;
+}
+
+/**
+ * Query the latest operation of an owner the contract rejected (read-only).
+ * Returns { code, message, timestamp } or null.
+ */
+export async function queryLastRejection(app, owner) {
+  const query = `query { lastRejection(owner: "${owner}") { code message timestamp } }`;
+  const result = await applicationQuery(app, query);
+  return result?.lastRejection || null;
+}
+
+/**
+ * Run a mutation and throw if the contract rejected the scheduled operation.
+ * Rejected operations don't fail the block, so compare the owner's last rejection
+ * before and after submitting.
+ */
+export async function submitOperation(app, mutation, owner = null) {
+  const before = owner ? await queryLastRejection(app, owner) : null;
+  const result = await applicationQuery(app, mutation);
+  if (owner) {
+    const after = await queryLastRejection(app, owner);
+    if (after && after.timestamp !== before?.timestamp) {
+      const error = new Error(after.message);
+      error.code = after.code;
+      throw error;
+    }
+  }
+  return result;
 }
 
 /**
@@ -205,25 +239,25 @@ export async function connectToTestnet(privateKeyHex = null) {
 /**
  * Deposit tokens into the casino contract.
  */
-export async function deposit(app, amount) {
+export async function deposit(app, amount, owner = null) {
   const amountAttos = Math.floor(parseFloat(amount) * 1e18).toString();
   const mutation = `mutation { deposit(amount: "${amountAttos}") }`;
-  return applicationQuery(app, mutation);
+  return submitOperation(app, mutation, owner);
 }
 
 /**
  * Withdraw tokens from the casino contract.
  */
-export async function withdraw(app, amount) {
+export async function withdraw(app, amount, owner = null) {
   const amountAttos = Math.floor(parseFloat(amount) * 1e18).toString();
   const mutation = `mutation { withdraw(amount: "${amountAttos}") }`;
-  return applicationQuery(app, mutation);
+  return submitOperation(app, mutation, owner);
 }
 
 /**
  * Place a bet on the casino contract.
  */
-export async function placeBet(app, gameType, betAmount, commitHash, gameParams = {}) {
+export async function placeBet(app, gameType, betAmount, commitHash, gameParams = {}, owner = null) {
   const amountAttos = Math.floor(parseFloat(betAmount) * 1e18).toString();
   const paramsJson = JSON.stringify(gameParams);
 
@@ -238,19 +272,19 @@ export async function placeBet(app, gameType, betAmount, commitHash, gameParams
     }
   `;
 
-  return applicationQuery(app, mutation);
+  return submitOperation(app, mutation, owner);
 }
 
 /**
  * Reveal the commit for a game.
  */
-export async function reveal(app, gameId, revealValue) {
+export async function reveal(app, gameId, revealValue, owner = null) {
   const mutation = `
     mutation {
       reveal(gameId: ${parseInt(gameId)}, revealValue: "${revealValue}")
     }
   `;
-  return applicationQuery(app, mutation);
+  return submitOperation(app, mutation, owner);
 }
 
 /**
@@ -300,6 +334,8 @@ export default {
   getChain,
   getApplication,
   applicationQuery,
+  queryLastRejection,
+  submitOperation,
   connectToTestnet,
   deposit,
   withdraw,
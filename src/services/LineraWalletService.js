@@ -539,7 +539,7 @@ class LineraWalletService {
 
       if (this.casinoApp) {
         // Use SDK Application.query() for direct contract interaction
-        const depositResult = await LineraClientService.deposit(this.casinoApp, depositAmount);
+        const depositResult = await LineraClientService.deposit(this.casinoApp, depositAmount, this.userOwner);
 
         result = {
           success: true,
@@ -600,7 +600,7 @@ class LineraWalletService {
 
       if (this.casinoApp) {
         // Use SDK Application.query() for direct contract interaction
-        const withdrawResult = await LineraClientService.withdraw(this.casinoApp, withdrawAmount);
+        const withdrawResult = await LineraClientService.withdraw(this.casinoApp, withdrawAmount, this.userOwner);
 
         result = {
           success: true,
@@ -878,7 +878,8 @@ class LineraWalletService {
             gameType,
             bet,
             commitHash,
-            gameParams
+            gameParams,
+            this.userOwner
           );
 
           const gameId = Date.now(); // Use timestamp as game ID
@@ -887,7 +888,8 @@ class LineraWalletService {
           const revealResult = await LineraClientService.reveal(
             this.casinoApp,
             gameId,
-            revealValue
+            revealValue,
+            this.userOwner
           );
 
           // Calculate outcome locally (same as contract)